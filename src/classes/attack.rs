@@ -0,0 +1,92 @@
+use super::score_manager::{ClearResult, ClearType};
+
+// Extra garbage for consecutive clears, indexed by how many clears in a row
+// came before this one
+const COMBO_ATTACK: [u32; 11] = [0, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5];
+
+// Sent on top of everything else for leaving the board empty
+const PERFECT_CLEAR_ATTACK: u32 = 10;
+
+// Garbage lines sent by a line clear, following the guideline attack table
+pub fn attack_lines(clear: &ClearResult) -> u32 {
+    let base = match clear.clear_type {
+        ClearType::None | ClearType::TSpinMini(0) | ClearType::TSpin(0) => return 0,
+        ClearType::Single => 0,
+        ClearType::Double => 1,
+        ClearType::Triple => 2,
+        ClearType::Tetris => 4,
+        ClearType::TSpinMini(1) => 0,
+        ClearType::TSpinMini(_) => 1,
+        ClearType::TSpin(1) => 2,
+        ClearType::TSpin(2) => 4,
+        ClearType::TSpin(_) => 6,
+    };
+
+    let b2b_bonus = if clear.back_to_back { 1 } else { 0 };
+    let chain = clear.combo.saturating_sub(1) as usize;
+    let combo_bonus = COMBO_ATTACK[chain.min(COMBO_ATTACK.len() - 1)];
+
+    let perfect_clear_bonus = if clear.perfect_clear { PERFECT_CLEAR_ATTACK } else { 0 };
+
+    base + b2b_bonus + combo_bonus + perfect_clear_bonus
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clear(clear_type: ClearType, back_to_back: bool, combo: u32, perfect_clear: bool) -> ClearResult {
+        ClearResult {
+            clear_type,
+            back_to_back,
+            back_to_back_chain: 0,
+            combo,
+            perfect_clear,
+            points: 0,
+        }
+    }
+
+    #[test]
+    fn first_clears_send_the_guideline_table() {
+        let cases = [
+            (ClearType::None, 0),
+            (ClearType::Single, 0),
+            (ClearType::Double, 1),
+            (ClearType::Triple, 2),
+            (ClearType::Tetris, 4),
+            (ClearType::TSpinMini(0), 0),
+            (ClearType::TSpinMini(1), 0),
+            (ClearType::TSpinMini(2), 1),
+            (ClearType::TSpin(0), 0),
+            (ClearType::TSpin(1), 2),
+            (ClearType::TSpin(2), 4),
+            (ClearType::TSpin(3), 6),
+        ];
+        for (clear_type, lines) in cases {
+            assert_eq!(attack_lines(&clear(clear_type, false, 1, false)), lines, "{:?}", clear_type);
+        }
+    }
+
+    #[test]
+    fn bonuses_stack_on_the_base_attack() {
+        let cases = [
+            // Back-to-back Tetris and T-spin double
+            (clear(ClearType::Tetris, true, 1, false), 5),
+            (clear(ClearType::TSpin(2), true, 1, false), 5),
+            // Combos count the clears before this one
+            (clear(ClearType::Single, false, 2, false), 1),
+            (clear(ClearType::Double, false, 4, false), 3),
+            (clear(ClearType::Single, false, 11, false), 5),
+            // The table stops growing at its last entry
+            (clear(ClearType::Single, false, 30, false), 5),
+            // Perfect clears send 10 on top of everything else
+            (clear(ClearType::Single, false, 1, true), 10),
+            (clear(ClearType::Tetris, true, 3, true), 16),
+            // Clears that clear nothing send nothing, whatever the state
+            (clear(ClearType::TSpin(0), true, 5, false), 0),
+        ];
+        for (result, lines) in cases {
+            assert_eq!(attack_lines(&result), lines, "{:?}", result);
+        }
+    }
+}
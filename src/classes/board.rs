@@ -3,12 +3,15 @@ use egui::Color32;
 const BOARD_WIDTH: usize = 10;
 const BOARD_HEIGHT: usize = 22;
 
+// Cell id for garbage rows, kept apart from the 1-7 piece ids
+pub const GARBAGE_ID: u8 = 8;
+
 #[derive(Clone, Copy, Debug)]
 pub struct Cell {
-    pub id: u8,  // 0 = empty, 1-7 = piece types
+    pub id: u8,  // 0 = empty, 1-7 = piece types, 8 = garbage
 }
 
-fn get_color_from_id(id: u8) -> Option<Color32> {
+pub fn get_color_from_id(id: u8) -> Option<Color32> {
     match id {
         0 => None,
         1 => Some(Color32::from_rgb(0, 255, 255)),   // I - Cyan
@@ -18,10 +21,12 @@ fn get_color_from_id(id: u8) -> Option<Color32> {
         5 => Some(Color32::from_rgb(255, 0, 0)),     // Z - Red
         6 => Some(Color32::from_rgb(0, 0, 255)),     // J - Blue
         7 => Some(Color32::from_rgb(255, 165, 0)),   // L - Orange
+        GARBAGE_ID => Some(Color32::from_rgb(110, 110, 110)), // Garbage - Gray
         _ => None,
     }
 }
 
+#[derive(Clone)]
 pub struct Board {
     pub cells: [[Cell; BOARD_WIDTH]; BOARD_HEIGHT],
     pub cell_size: f32,
@@ -41,12 +46,30 @@ impl Board {
 
     // Draw board with optional overlay blocks (for ghost pieces)
     pub fn draw_with_overlay(&self, ui: &mut egui::Ui, overlay_blocks: &[(i32, i32, Color32)]) {
-        let (rect, _response) = ui.allocate_exact_size(
+        self.paint(ui, overlay_blocks, egui::Sense::hover());
+    }
+
+    // Draw the board for the editor. Gives the cell under the pointer while a
+    // mouse button is held on the board, with whether it's the secondary one.
+    pub fn draw_editable(&self, ui: &mut egui::Ui) -> Option<(usize, usize, bool)> {
+        let response = self.paint(ui, &[], egui::Sense::click_and_drag());
+        let offset = response.interact_pointer_pos()? - response.rect.min;
+        if offset.x < 0.0 || offset.y < 0.0 {
+            return None;
+        }
+
+        let (row, col) = ((offset.y / self.cell_size) as usize, (offset.x / self.cell_size) as usize);
+        let secondary = ui.input(|i| i.pointer.secondary_down());
+        (row < BOARD_HEIGHT && col < BOARD_WIDTH).then_some((row, col, secondary))
+    }
+
+    fn paint(&self, ui: &mut egui::Ui, overlay_blocks: &[(i32, i32, Color32)], sense: egui::Sense) -> egui::Response {
+        let (rect, response) = ui.allocate_exact_size(
             egui::vec2(
                 BOARD_WIDTH as f32 * self.cell_size,
                 BOARD_HEIGHT as f32 * self.cell_size,
             ),
-            egui::Sense::hover(),
+            sense,
         );
 
         let painter = ui.painter();
@@ -101,6 +124,8 @@ impl Board {
             0.0,
             egui::Stroke::new(2.0, Color32::from_rgb(100, 100, 120)),
         );
+
+        response
     }
 
     pub fn set_cell(&mut self, row: usize, col: usize, id: u8) {
@@ -117,6 +142,26 @@ impl Board {
         }
     }
 
+    // Out of bounds counts as occupied, like walls and floor do for kicks and T-spin corners
+    pub fn is_occupied(&self, row: i32, col: i32) -> bool {
+        row < 0 || row >= BOARD_HEIGHT as i32
+            || col < 0 || col >= BOARD_WIDTH as i32
+            || self.get_cell(row as usize, col as usize) != 0
+    }
+
+    // Cell ids row by row, the form boards are sent over the network in
+    pub fn to_ids(&self) -> Vec<u8> {
+        self.cells.iter().flatten().map(|cell| cell.id).collect()
+    }
+
+    pub fn from_ids(ids: &[u8]) -> Self {
+        let mut board = Self::new();
+        for (i, id) in ids.iter().take(BOARD_WIDTH * BOARD_HEIGHT).enumerate() {
+            board.cells[i / BOARD_WIDTH][i % BOARD_WIDTH].id = *id;
+        }
+        board
+    }
+
     pub fn get_width() -> usize { BOARD_WIDTH }
     pub fn get_height() -> usize { BOARD_HEIGHT }
 
@@ -128,6 +173,44 @@ impl Board {
         })
     }
 
+    // Push the stack up one row and fill the bottom with garbage, leaving a hole
+    // at `hole`. Returns false if a filled cell was pushed off the top.
+    pub fn insert_garbage_row(&mut self, hole: usize) -> bool {
+        let overflowed = self.cells[0].iter().any(|cell| cell.id != 0);
+
+        for r in 0..BOARD_HEIGHT - 1 {
+            self.cells[r] = self.cells[r + 1];
+        }
+        let mut row = [Cell { id: GARBAGE_ID }; BOARD_WIDTH];
+        if hole < BOARD_WIDTH {
+            row[hole].id = 0;
+        }
+        self.cells[BOARD_HEIGHT - 1] = row;
+
+        !overflowed
+    }
+
+    // Nothing left on the board, a perfect clear after a line clear
+    pub fn is_empty(&self) -> bool {
+        self.cells.iter().flatten().all(|cell| cell.id == 0)
+    }
+
+    // Rows from the floor up to the highest filled cell
+    pub fn get_stack_height(&self) -> usize {
+        self.cells
+            .iter()
+            .position(|row| row.iter().any(|cell| cell.id != 0))
+            .map_or(0, |top| BOARD_HEIGHT - top)
+    }
+
+    // Rows that still have garbage in them
+    pub fn count_garbage_rows(&self) -> usize {
+        self.cells
+            .iter()
+            .filter(|row| row.iter().any(|cell| cell.id == GARBAGE_ID))
+            .count()
+    }
+
     pub fn clear_lines(&mut self) -> u32 {
         let mut lines_cleared = 0;
         let mut row = BOARD_HEIGHT - 1; // Start from bottom
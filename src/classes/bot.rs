@@ -0,0 +1,306 @@
+use super::board::Board;
+use super::engine::{Engine, FRAME_TIME};
+use super::piece::Piece;
+use crate::enums::cpu_difficulty::CpuDifficulty;
+use crate::enums::game_actions::GameAction;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// Placement weights, the height, line, hole and bumpiness ones are the usual
+// single piece lookahead values
+const AGGREGATE_HEIGHT_WEIGHT: f32 = -0.51;
+const LINES_CLEARED_WEIGHT: f32 = 0.76;
+const HOLES_WEIGHT: f32 = -0.36;
+const BUMPINESS_WEIGHT: f32 = -0.18;
+// Building a slot for a T-spin double is worth a little, having several isn't
+const T_SLOT_WEIGHT: f32 = 0.4;
+// Extra cost per row the stack reaches above DANGER_HEIGHT, so garbage can't top it out
+const DANGER_WEIGHT: f32 = -1.0;
+const DANGER_HEIGHT: usize = 14;
+
+// A mistake takes one of this many runner-up placements instead of the best
+const MISTAKE_CHOICES: usize = 5;
+
+// Rotation inputs tried before shifting, one per orientation
+const ROTATIONS: [&[GameAction]; 4] = [
+    &[],
+    &[GameAction::RotateCW],
+    &[GameAction::RotateCW, GameAction::RotateCW],
+    &[GameAction::RotateCCW],
+];
+
+// One way to place a piece and the inputs that get it there
+#[derive(Debug, Clone)]
+struct Placement {
+    actions: Vec<GameAction>,
+    score: f32,
+}
+
+// CPU player for versus. Each new piece is moved into the best spot found by
+// trying every rotation and column on the current board, then hard dropped
+// once the difficulty's pieces per second cap allows it.
+pub struct Bot {
+    difficulty: CpuDifficulty,
+    rng: StdRng,
+    // Pieces placed when the current piece was moved into place
+    positioned_for: Option<u32>,
+    // Engine frame the next hard drop is allowed on
+    next_drop_frame: u64,
+}
+
+impl Bot {
+    // Mistakes are rolled from the game's seed, so the same deal plays out the same way
+    pub fn new(difficulty: CpuDifficulty, seed: u64) -> Self {
+        let mut bot = Self {
+            difficulty,
+            rng: StdRng::seed_from_u64(seed),
+            positioned_for: None,
+            next_drop_frame: 0,
+        };
+        bot.reset(seed);
+        bot
+    }
+
+    // Forget the last game, called when a new one starts from `seed`
+    pub fn reset(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.positioned_for = None;
+        self.next_drop_frame = self.drop_interval_frames();
+    }
+
+    // Play whatever inputs are due on this frame
+    pub fn update(&mut self, engine: &mut Engine) {
+        if engine.is_game_over() || engine.current_piece.is_none() {
+            return;
+        }
+
+        let placed = engine.get_pieces_placed();
+        if self.positioned_for != Some(placed) {
+            self.position_piece(engine);
+            self.positioned_for = Some(placed);
+        }
+
+        // The piece can sit in place until the speed cap lets it drop, gravity
+        // only takes it further down the same column
+        let frame = engine.get_frame();
+        if frame >= self.next_drop_frame {
+            engine.press(GameAction::HardDrop);
+            self.next_drop_frame = frame + self.drop_interval_frames();
+        }
+    }
+
+    fn drop_interval_frames(&self) -> u64 {
+        (1.0 / (self.difficulty.pieces_per_second() * FRAME_TIME)).round() as u64
+    }
+
+    // Hold if the other piece has a better spot, then shift and rotate into place
+    fn position_piece(&mut self, engine: &mut Engine) {
+        let Some(piece) = engine.current_piece.clone() else {
+            return;
+        };
+
+        if engine.hold_queue.can_hold() {
+            let swapped_in = engine.hold_queue.held_piece.clone()
+                .or_else(|| engine.queue.peek().cloned());
+            if let Some(other) = swapped_in {
+                let best = best_score(&placements(&engine.board, &piece));
+                let best_other = best_score(&placements(&engine.board, &other));
+                if best_other > best {
+                    engine.press(GameAction::HoldPiece);
+                }
+            }
+        }
+
+        let Some(piece) = engine.current_piece.clone() else {
+            return;
+        };
+        if let Some(placement) = self.pick(placements(&engine.board, &piece)) {
+            for action in placement.actions {
+                engine.press(action);
+                engine.release(action);
+            }
+        }
+    }
+
+    // The best placement, or now and then one of the next best
+    fn pick(&mut self, mut placements: Vec<Placement>) -> Option<Placement> {
+        placements.sort_by(|a, b| b.score.total_cmp(&a.score));
+        let mut index = 0;
+        if placements.len() > 1 && self.rng.gen_bool(self.difficulty.mistake_rate()) {
+            index = self.rng.gen_range(1..placements.len().min(MISTAKE_CHOICES + 1));
+        }
+        (index < placements.len()).then(|| placements.swap_remove(index))
+    }
+}
+
+fn best_score(placements: &[Placement]) -> f32 {
+    placements.iter().map(|p| p.score).fold(f32::NEG_INFINITY, f32::max)
+}
+
+// Every distinct spot the piece can be hard dropped into by rotating first and
+// then shifting, simulated with the same kicks and collisions as the engine
+fn placements(board: &Board, piece: &Piece) -> Vec<Placement> {
+    let mut placements = Vec::new();
+    let mut seen: Vec<Vec<(i32, i32)>> = Vec::new();
+
+    for rotation in ROTATIONS {
+        let mut rotated = piece.clone();
+        if !rotation.iter().all(|action| rotated.rotate(*action == GameAction::RotateCW, board).is_some()) {
+            continue;
+        }
+
+        for (shift, direction) in [(GameAction::MoveLeft, -1), (GameAction::MoveRight, 1)] {
+            let mut shifted = rotated.clone();
+            let mut actions = rotation.to_vec();
+            loop {
+                let mut landed = shifted.clone();
+                while board.is_valid_position(&landed.get_blocks()) {
+                    landed.move_down();
+                }
+                landed.move_up();
+
+                let mut blocks = landed.get_blocks();
+                blocks.sort();
+                if !seen.contains(&blocks) {
+                    let mut after = board.clone();
+                    for (row, col) in &blocks {
+                        after.set_cell(*row as usize, *col as usize, piece.get_id());
+                    }
+                    let cleared = after.clear_lines();
+                    placements.push(Placement { actions: actions.clone(), score: evaluate(&after, cleared) });
+                    seen.push(blocks);
+                }
+
+                if direction < 0 { shifted.move_left() } else { shifted.move_right() }
+                if !board.is_valid_position(&shifted.get_blocks()) {
+                    break;
+                }
+                actions.push(shift);
+            }
+        }
+    }
+
+    placements
+}
+
+// Score a board after a placement, higher is better
+fn evaluate(board: &Board, cleared: u32) -> f32 {
+    let width = Board::get_width();
+    let height = Board::get_height();
+
+    let heights: Vec<usize> = (0..width)
+        .map(|col| {
+            (0..height)
+                .find(|row| board.get_cell(*row, col) != 0)
+                .map_or(0, |row| height - row)
+        })
+        .collect();
+
+    let aggregate_height: usize = heights.iter().sum();
+    let bumpiness: usize = heights.windows(2).map(|pair| pair[0].abs_diff(pair[1])).sum();
+    let holes: usize = (0..width)
+        .map(|col| {
+            (height - heights[col]..height)
+                .filter(|row| board.get_cell(*row, col) == 0)
+                .count()
+        })
+        .sum();
+    let max_height = heights.iter().copied().max().unwrap_or(0);
+    let danger = max_height.saturating_sub(DANGER_HEIGHT);
+    let t_slots = count_t_slots(board).min(1);
+
+    AGGREGATE_HEIGHT_WEIGHT * aggregate_height as f32
+        + LINES_CLEARED_WEIGHT * cleared as f32
+        + HOLES_WEIGHT * holes as f32
+        + BUMPINESS_WEIGHT * bumpiness as f32
+        + T_SLOT_WEIGHT * t_slots as f32
+        + DANGER_WEIGHT * danger as f32
+}
+
+// Slots a T could spin into for a double: three open cells over a row with
+// only its middle open, and one overhang roofing the slot from above
+fn count_t_slots(board: &Board) -> usize {
+    let filled = |row: i32, col: i32| board.is_occupied(row, col);
+    let mut slots = 0;
+
+    for row in 1..Board::get_height() as i32 - 1 {
+        for col in 0..Board::get_width() as i32 - 2 {
+            let middle_open = !filled(row, col) && !filled(row, col + 1) && !filled(row, col + 2);
+            let bottom_slot = filled(row + 1, col) && !filled(row + 1, col + 1) && filled(row + 1, col + 2);
+            let roofed = !filled(row - 1, col + 1) && (filled(row - 1, col) != filled(row - 1, col + 2));
+            if middle_open && bottom_slot && roofed {
+                slots += 1;
+            }
+        }
+    }
+
+    slots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classes::board::GARBAGE_ID;
+
+    // Board with `rows` at the bottom, '#' marks a filled cell
+    fn board(rows: &[&str]) -> Board {
+        let mut board = Board::new();
+        let top = Board::get_height() - rows.len();
+        for (i, row) in rows.iter().enumerate() {
+            for (col, cell) in row.chars().enumerate() {
+                if cell == '#' {
+                    board.set_cell(top + i, col, GARBAGE_ID);
+                }
+            }
+        }
+        board
+    }
+
+    #[test]
+    fn a_hole_scores_below_a_flat_stack() {
+        let flat = board(&[
+            "####......",
+        ]);
+        let hole = board(&[
+            "..#.......",
+            "##.#......",
+        ]);
+        assert!(evaluate(&hole, 0) < evaluate(&flat, 0));
+    }
+
+    #[test]
+    fn count_t_slots_finds_a_t_spin_double_slot() {
+        let slot = board(&[
+            "####......",
+            "###...####",
+            "####.#####",
+        ]);
+        assert_eq!(count_t_slots(&slot), 1);
+
+        // Without the overhang a T can just drop in, so it isn't a slot
+        let open = board(&[
+            "###...####",
+            "####.#####",
+        ]);
+        assert_eq!(count_t_slots(&open), 0);
+        assert_eq!(count_t_slots(&Board::new()), 0);
+    }
+
+    #[test]
+    fn no_mistakes_always_picks_the_best_placement() {
+        assert_eq!(CpuDifficulty::Expert.mistake_rate(), 0.0);
+        let stack = board(&[
+            "#...##...#",
+            "##.####.##",
+        ]);
+        let options = placements(&stack, &Piece::create_piece("T"));
+        let best = best_score(&options);
+        assert!(options.iter().any(|p| p.score < best));
+
+        for seed in 0..50 {
+            let mut bot = Bot::new(CpuDifficulty::Expert, seed);
+            let picked = bot.pick(options.clone()).unwrap();
+            assert_eq!(picked.score, best, "seed {}", seed);
+        }
+    }
+}
@@ -1,7 +1,11 @@
 use rusqlite::{params, Connection, Result};
 use crate::classes::player::{GameStats};
 use crate::classes::player::Player;
-use crate::classes::database::schemas::{players, games};    
+use crate::classes::randomizer::RandomizerKind;
+use crate::classes::database::schemas::{players, games, replays, versus_matches, puzzles};
+use crate::classes::puzzle::Puzzle;
+use crate::classes::replay::Replay;
+use crate::enums::game_modes::GameMode;
 use std::time::{SystemTime, UNIX_EPOCH};
 pub struct DbManager {
     // Database connection and related fields
@@ -22,8 +26,31 @@ impl DbManager {
         // Create tables for players and games if they don't exist
         self.conn.execute_batch(players::CREATE_PLAYERS_TABLE).unwrap();
         self.conn.execute_batch(games::CREATE_GAMES_TABLE).unwrap();
+        self.add_missing_columns("games", games::ADDED_GAMES_COLUMNS).unwrap();
+        self.conn.execute_batch(replays::CREATE_REPLAYS_TABLE).unwrap();
+        self.conn.execute_batch(versus_matches::CREATE_VERSUS_MATCHES_TABLE).unwrap();
+        self.conn.execute_batch(puzzles::CREATE_PUZZLES_TABLE).unwrap();
     }
 
+    // Bring tables created by older versions up to date
+    fn add_missing_columns(&self, table: &str, columns: &[(&str, &str)]) -> Result<()> {
+        let mut stmt = self.conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let existing = stmt
+            .query_map(params![], |row| row.get::<_, String>(1))?
+            .collect::<Result<Vec<String>>>()?;
+
+        for (name, definition) in columns {
+            if !existing.iter().any(|column| column == name) {
+                self.conn.execute(
+                    &format!("ALTER TABLE {} ADD COLUMN {} {}", table, name, definition),
+                    params![],
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    #[allow(dead_code)]
     pub fn get_connection(&self) -> &Connection {
         &self.conn
     }
@@ -40,6 +67,7 @@ impl DbManager {
         Ok(self.conn.last_insert_rowid())
     }
 
+    #[allow(dead_code)]
     pub fn get_player_id(&self, name: &str) -> Result<Option<i64>> {
         let mut stmt = self.conn.prepare("SELECT id FROM players WHERE name = ?1")?;
         let mut rows = stmt.query(params![name])?;
@@ -63,15 +91,20 @@ impl DbManager {
         }
     }
 
-    pub fn save_game(&self, player_id: i64, game_stats: &GameStats) -> Result<()> {
+    // Returns the new game's id
+    pub fn save_game(&self, player_id: i64, game_stats: &GameStats) -> Result<i64> {
         let now = SystemTime::now()
     .duration_since(UNIX_EPOCH)
     .unwrap()
     .as_secs() as i64;
         
         self.conn.execute(
-            "INSERT INTO games (player_id, score, level, lines_cleared, singles, doubles, triples, quadruples, max_combo, pieces_placed, duration_seconds, back_to_backs, played_at) 
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            "INSERT INTO games (player_id, score, level, lines_cleared, singles, doubles, triples, quadruples, max_combo, pieces_placed, duration_seconds, back_to_backs, played_at,
+                                t_spin_zeros, t_spin_singles, t_spin_doubles, t_spin_triples, t_spin_minis, seed, randomizer,
+                                mode, finish_time_ms, finesse_faults, key_presses, attack_sent, pps, kpp, lines_per_minute, attack_per_minute,
+                                play_time_ms, longest_back_to_back, perfect_clears) 
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23,
+                     ?24, ?25, ?26, ?27, ?28, ?29, ?30, ?31, ?32)",
             params![
                 player_id,
                 game_stats.score as i64,
@@ -85,12 +118,108 @@ impl DbManager {
                 game_stats.pieces_placed as i64,
                 game_stats.duration_seconds as i64,
                 game_stats.back_to_backs as i64,
-                now
+                now,
+                game_stats.t_spin_zeros as i64,
+                game_stats.t_spin_singles as i64,
+                game_stats.t_spin_doubles as i64,
+                game_stats.t_spin_triples as i64,
+                game_stats.t_spin_minis as i64,
+                // SQLite integers are signed, the seed's bits are kept as-is
+                game_stats.seed as i64,
+                game_stats.randomizer.db_name(),
+                game_stats.mode.db_name(),
+                game_stats.finish_time_ms.map(|ms| ms as i64),
+                game_stats.finesse_faults as i64,
+                game_stats.key_presses as i64,
+                game_stats.attack_sent as i64,
+                game_stats.pps as f64,
+                game_stats.kpp as f64,
+                game_stats.lines_per_minute as f64,
+                game_stats.attack_per_minute as f64,
+                game_stats.play_time_ms as i64,
+                game_stats.longest_back_to_back as i64,
+                game_stats.perfect_clears as i64,
             ],
         )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn save_replay(&self, game_id: i64, replay: &Replay) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO replays (game_id, data) VALUES (?1, ?2)",
+            params![game_id, replay.to_json()],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_replay(&self, game_id: i64) -> Result<Option<Replay>> {
+        let mut stmt = self.conn.prepare("SELECT data FROM replays WHERE game_id = ?1")?;
+        let mut rows = stmt.query(params![game_id])?;
+
+        if let Some(row) = rows.next()? {
+            let data: String = row.get(0)?;
+            Ok(Replay::from_json(&data))
+        } else {
+            Ok(None)
+        }
+    }
+
+    // Saving under a name that's already taken replaces that puzzle
+    pub fn save_puzzle(&self, puzzle: &Puzzle) -> Result<()> {
+        let now = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap()
+    .as_secs() as i64;
+
+        self.conn.execute(
+            "INSERT INTO puzzles (name, data, created_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(name) DO UPDATE SET data = excluded.data",
+            params![puzzle.name, puzzle.to_json(), now],
+        )?;
+        Ok(())
+    }
+
+    // Saved puzzles by name, any that no longer parse are left out
+    pub fn get_puzzles(&self) -> Result<Vec<Puzzle>> {
+        let mut stmt = self.conn.prepare("SELECT data FROM puzzles ORDER BY name")?;
+        let puzzles = stmt
+            .query_map(params![], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<String>>>()?;
+        Ok(puzzles.iter().filter_map(|data| Puzzle::from_json(data)).collect())
+    }
+
+    pub fn delete_puzzle(&self, name: &str) -> Result<()> {
+        self.conn.execute("DELETE FROM puzzles WHERE name = ?1", params![name])?;
+        Ok(())
+    }
+
+    // winner_id is None for a draw
+    pub fn save_versus_match(&self, player1_id: i64, player2_id: i64, winner_id: Option<i64>) -> Result<()> {
+        let now = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap()
+    .as_secs() as i64;
+
+        self.conn.execute(
+            "INSERT INTO versus_matches (player1_id, player2_id, winner_id, played_at) VALUES (?1, ?2, ?3, ?4)",
+            params![player1_id, player2_id, winner_id, now],
+        )?;
         Ok(())
     }
 
+    // (wins, losses) over all versus matches, draws count as neither
+    pub fn get_versus_record(&self, player_id: i64) -> Result<(u32, u32)> {
+        self.conn.query_row(
+            "SELECT
+                COALESCE(SUM(winner_id = ?1), 0),
+                COALESCE(SUM(winner_id IS NOT NULL AND winner_id != ?1), 0)
+             FROM versus_matches
+             WHERE player1_id = ?1 OR player2_id = ?1",
+            params![player_id],
+            |row| Ok((row.get::<_, i64>(0)? as u32, row.get::<_, i64>(1)? as u32)),
+        )
+    }
+
     pub fn get_last_active_player(&self) -> Result<Option<Player>> {
         // First try to get the player_id from the most recent game
         let mut stmt = self.conn.prepare(
@@ -131,6 +260,9 @@ impl DbManager {
 
             // Load all games for this player and build stats
             self.load_player_stats(&mut player)?;
+            let (wins, losses) = self.get_versus_record(player_id)?;
+            player.stats.versus_wins = wins;
+            player.stats.versus_losses = losses;
 
             Ok(Some(player))
         } else {
@@ -143,7 +275,10 @@ impl DbManager {
         
         let mut stmt = self.conn.prepare(
             "SELECT score, level, lines_cleared, singles, doubles, triples, quadruples, 
-                    max_combo, pieces_placed, duration_seconds, played_at, back_to_backs
+                    max_combo, pieces_placed, duration_seconds, played_at, back_to_backs,
+                    t_spin_zeros, t_spin_singles, t_spin_doubles, t_spin_triples, t_spin_minis, seed, randomizer,
+                    mode, finish_time_ms, finesse_faults, key_presses, attack_sent, pps, kpp, lines_per_minute, attack_per_minute,
+                    play_time_ms, longest_back_to_back, perfect_clears
              FROM games 
              WHERE player_id = ?1 
              ORDER BY played_at ASC"
@@ -163,6 +298,27 @@ impl DbManager {
                 duration_seconds: row.get::<_, i64>(9)? as u64,
                 timestamp: row.get::<_, i64>(10)? as u64,
                 back_to_backs: row.get::<_, i64>(11).unwrap_or(0) as u32,
+                t_spin_zeros: row.get::<_, i64>(12)? as u32,
+                t_spin_singles: row.get::<_, i64>(13)? as u32,
+                t_spin_doubles: row.get::<_, i64>(14)? as u32,
+                t_spin_triples: row.get::<_, i64>(15)? as u32,
+                t_spin_minis: row.get::<_, i64>(16)? as u32,
+                seed: row.get::<_, i64>(17)? as u64,
+                randomizer: RandomizerKind::from_db_name(&row.get::<_, String>(18)?)
+                    .unwrap_or(RandomizerKind::SevenBag),
+                mode: GameMode::from_db_name(&row.get::<_, String>(19)?)
+                    .unwrap_or(GameMode::Marathon),
+                finish_time_ms: row.get::<_, Option<i64>>(20)?.map(|ms| ms as u64),
+                finesse_faults: row.get::<_, i64>(21)? as u32,
+                key_presses: row.get::<_, i64>(22)? as u32,
+                attack_sent: row.get::<_, i64>(23)? as u32,
+                pps: row.get::<_, f64>(24)? as f32,
+                kpp: row.get::<_, f64>(25)? as f32,
+                lines_per_minute: row.get::<_, f64>(26)? as f32,
+                attack_per_minute: row.get::<_, f64>(27)? as f32,
+                play_time_ms: row.get::<_, i64>(28)? as u64,
+                longest_back_to_back: row.get::<_, i64>(29)? as u32,
+                perfect_clears: row.get::<_, i64>(30)? as u32,
             })
         })?;
 
@@ -188,7 +344,8 @@ impl DbManager {
         Ok(players)
     }
 
-    pub fn get_leaderboard(&self, limit: usize, category: LeaderboardCategory) -> Result<Vec<(String, u32)>> {
+    // Rankings for one category, optionally only counting games dealt by one randomizer
+    pub fn get_leaderboard(&self, limit: usize, category: LeaderboardCategory, randomizer: Option<RandomizerKind>) -> Result<Vec<LeaderboardEntry>> {
         let (column, alias) = match category {
             LeaderboardCategory::HighScore => ("MAX(g.score)", "value"),
             LeaderboardCategory::HighestLevel => ("MAX(g.level)", "value"),
@@ -196,21 +353,41 @@ impl DbManager {
             LeaderboardCategory::MaxCombo => ("MAX(g.max_combo)", "value"),
             LeaderboardCategory::Mostquadruples => ("MAX(g.quadruples)", "value"),
             LeaderboardCategory::MostBackToBacks => ("MAX(g.back_to_backs)", "value"),
+            LeaderboardCategory::LongestBackToBack => ("MAX(g.longest_back_to_back)", "value"),
+            LeaderboardCategory::MostPerfectClears => ("MAX(g.perfect_clears)", "value"),
+            LeaderboardCategory::SprintTime => ("MIN(g.finish_time_ms)", "value"),
+            LeaderboardCategory::UltraScore => ("MAX(g.score)", "value"),
+            LeaderboardCategory::DigTime => ("MIN(g.finish_time_ms)", "value"),
+            LeaderboardCategory::SurvivalTime => ("MAX(g.finish_time_ms)", "value"),
         };
+        let mode = category.mode();
+        let order = if category.lower_is_better() { "ASC" } else { "DESC" };
 
+        // With a single MAX()/MIN() aggregate SQLite takes the bare g.id from the
+        // best game, so each entry links to the game that set it. Unfinished
+        // games have a NULL time and are ignored by MIN().
         let query = format!(
-            "SELECT p.name, {} as {}
+            "SELECT p.name, {} as {}, g.id, r.game_id IS NOT NULL
              FROM players p
              JOIN games g ON p.id = g.player_id
+             LEFT JOIN replays r ON r.game_id = g.id
+             WHERE (?2 IS NULL OR g.randomizer = ?2) AND g.mode = ?3
              GROUP BY p.id
-             ORDER BY {} DESC
+             HAVING {} IS NOT NULL
+             ORDER BY {} {}
              LIMIT ?1",
-            column, alias, alias
+            column, alias, alias, alias, order
         );
 
         let mut stmt = self.conn.prepare(&query)?;
-        let leaderboard_iter = stmt.query_map(params![limit as i64], |row| {
-            Ok((row.get(0)?, row.get(1)?))
+        let randomizer = randomizer.map(|kind| kind.db_name());
+        let leaderboard_iter = stmt.query_map(params![limit as i64, randomizer, mode.db_name()], |row| {
+            Ok(LeaderboardEntry {
+                player_name: row.get(0)?,
+                value: row.get(1)?,
+                game_id: row.get(2)?,
+                has_replay: row.get(3)?,
+            })
         })?;
 
         let mut leaderboard = Vec::new();
@@ -222,6 +399,13 @@ impl DbManager {
     }
 }
 
+pub struct LeaderboardEntry {
+    pub player_name: String,
+    pub value: u32,
+    pub game_id: i64,
+    pub has_replay: bool,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum LeaderboardCategory {
     HighScore,
@@ -230,4 +414,36 @@ pub enum LeaderboardCategory {
     MaxCombo,
     Mostquadruples,
     MostBackToBacks,
-}
\ No newline at end of file
+    LongestBackToBack,
+    MostPerfectClears,
+    SprintTime,
+    UltraScore,
+    DigTime,
+    SurvivalTime,
+}
+
+impl LeaderboardCategory {
+    // Game mode whose games the category ranks
+    pub fn mode(&self) -> GameMode {
+        match self {
+            LeaderboardCategory::SprintTime => GameMode::Sprint,
+            LeaderboardCategory::UltraScore => GameMode::Ultra,
+            LeaderboardCategory::DigTime => GameMode::Dig,
+            LeaderboardCategory::SurvivalTime => GameMode::Survival,
+            _ => GameMode::Marathon,
+        }
+    }
+
+    // Whether the value is a time in milliseconds
+    pub fn is_time(&self) -> bool {
+        matches!(
+            self,
+            LeaderboardCategory::SprintTime | LeaderboardCategory::DigTime | LeaderboardCategory::SurvivalTime
+        )
+    }
+
+    // Race categories rank the fastest finish first
+    pub fn lower_is_better(&self) -> bool {
+        matches!(self, LeaderboardCategory::SprintTime | LeaderboardCategory::DigTime)
+    }
+}
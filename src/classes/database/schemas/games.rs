@@ -14,6 +14,48 @@ pub const CREATE_GAMES_TABLE: &str = "
         duration_seconds INTEGER NOT NULL,
         back_to_backs INTEGER NOT NULL,
         played_at INTEGER NOT NULL,
+        t_spin_zeros INTEGER NOT NULL DEFAULT 0,
+        t_spin_singles INTEGER NOT NULL DEFAULT 0,
+        t_spin_doubles INTEGER NOT NULL DEFAULT 0,
+        t_spin_triples INTEGER NOT NULL DEFAULT 0,
+        t_spin_minis INTEGER NOT NULL DEFAULT 0,
+        seed INTEGER NOT NULL DEFAULT 0,
+        randomizer TEXT NOT NULL DEFAULT 'seven_bag',
+        mode TEXT NOT NULL DEFAULT 'marathon',
+        finish_time_ms INTEGER,
+        finesse_faults INTEGER NOT NULL DEFAULT 0,
+        key_presses INTEGER NOT NULL DEFAULT 0,
+        attack_sent INTEGER NOT NULL DEFAULT 0,
+        pps REAL NOT NULL DEFAULT 0,
+        kpp REAL NOT NULL DEFAULT 0,
+        lines_per_minute REAL NOT NULL DEFAULT 0,
+        attack_per_minute REAL NOT NULL DEFAULT 0,
+        play_time_ms INTEGER NOT NULL DEFAULT 0,
+        longest_back_to_back INTEGER NOT NULL DEFAULT 0,
+        perfect_clears INTEGER NOT NULL DEFAULT 0,
         FOREIGN KEY(player_id) REFERENCES players(id)
     );
-";
\ No newline at end of file
+";
+
+// Columns added after the original table, applied to existing databases on startup
+pub const ADDED_GAMES_COLUMNS: &[(&str, &str)] = &[
+    ("t_spin_zeros", "INTEGER NOT NULL DEFAULT 0"),
+    ("t_spin_singles", "INTEGER NOT NULL DEFAULT 0"),
+    ("t_spin_doubles", "INTEGER NOT NULL DEFAULT 0"),
+    ("t_spin_triples", "INTEGER NOT NULL DEFAULT 0"),
+    ("t_spin_minis", "INTEGER NOT NULL DEFAULT 0"),
+    ("seed", "INTEGER NOT NULL DEFAULT 0"),
+    ("randomizer", "TEXT NOT NULL DEFAULT 'seven_bag'"),
+    ("mode", "TEXT NOT NULL DEFAULT 'marathon'"),
+    ("finish_time_ms", "INTEGER"),
+    ("finesse_faults", "INTEGER NOT NULL DEFAULT 0"),
+    ("key_presses", "INTEGER NOT NULL DEFAULT 0"),
+    ("attack_sent", "INTEGER NOT NULL DEFAULT 0"),
+    ("pps", "REAL NOT NULL DEFAULT 0"),
+    ("kpp", "REAL NOT NULL DEFAULT 0"),
+    ("lines_per_minute", "REAL NOT NULL DEFAULT 0"),
+    ("attack_per_minute", "REAL NOT NULL DEFAULT 0"),
+    ("play_time_ms", "INTEGER NOT NULL DEFAULT 0"),
+    ("longest_back_to_back", "INTEGER NOT NULL DEFAULT 0"),
+    ("perfect_clears", "INTEGER NOT NULL DEFAULT 0"),
+];
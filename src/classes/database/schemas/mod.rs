@@ -1,2 +1,5 @@
 pub mod players;
-pub mod games;
\ No newline at end of file
+pub mod games;
+pub mod replays;
+pub mod versus_matches;
+pub mod puzzles;
\ No newline at end of file
@@ -0,0 +1,9 @@
+// Board editor puzzles, stored as JSON like replays
+pub const CREATE_PUZZLES_TABLE: &str = "
+    CREATE TABLE IF NOT EXISTS puzzles (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE,
+        data TEXT NOT NULL,
+        created_at INTEGER NOT NULL
+    );
+";
@@ -0,0 +1,7 @@
+pub const CREATE_REPLAYS_TABLE: &str = "
+    CREATE TABLE IF NOT EXISTS replays (
+        game_id INTEGER PRIMARY KEY,
+        data TEXT NOT NULL,
+        FOREIGN KEY(game_id) REFERENCES games(id)
+    );
+";
@@ -0,0 +1,13 @@
+// A NULL winner_id is a draw
+pub const CREATE_VERSUS_MATCHES_TABLE: &str = "
+    CREATE TABLE IF NOT EXISTS versus_matches (
+        id INTEGER PRIMARY KEY,
+        player1_id INTEGER NOT NULL,
+        player2_id INTEGER NOT NULL,
+        winner_id INTEGER,
+        played_at INTEGER NOT NULL,
+        FOREIGN KEY(player1_id) REFERENCES players(id),
+        FOREIGN KEY(player2_id) REFERENCES players(id),
+        FOREIGN KEY(winner_id) REFERENCES players(id)
+    );
+";
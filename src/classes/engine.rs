@@ -0,0 +1,1247 @@
+use super::board::Board;
+use super::piece::Piece;
+use super::queue::Queue;
+use super::score_manager::{ScoreManager, TSpin};
+use super::hold::HoldQueue;
+use super::player::GameStats;
+use super::replay::{Replay, ReplayEvent};
+use super::master::{self, MASTER_MAX_LEVEL};
+use super::attack;
+use super::finesse::{self, FinesseResult};
+use super::perfect_clear::{PC_HEIGHT, PC_PREVIEW};
+use super::opener::{Opener, OpenerStep, PlacementGrade};
+use crate::enums::game_actions::GameAction;
+use crate::enums::game_modes::GameMode;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+// The engine advances in fixed 60 Hz frames so the same inputs always give the same game
+pub const FRAME_TIME: f32 = 1.0 / 60.0;
+
+// Lines to clear to finish a Sprint
+pub const SPRINT_LINES: u32 = 40;
+
+// Length of an Ultra game in frames (2 minutes)
+pub const ULTRA_FRAMES: u64 = 2 * 60 * 60;
+
+// Garbage rows to clear in Dig
+pub const DIG_GARBAGE_ROWS: usize = 10;
+
+// Survival pushes a garbage row in on a timer that speeds up with every row, in frames
+const SURVIVAL_START_INTERVAL: u32 = 5 * 60;
+const SURVIVAL_MIN_INTERVAL: u32 = 60;
+const SURVIVAL_SPEEDUP: f32 = 0.95;
+
+// Mixed into the game seed so garbage holes don't follow the piece sequence
+const GARBAGE_SEED_SALT: u64 = 0x9E37_79B9_7F4A_7C15;
+
+// Marathon lengths offered besides a custom cap
+pub const MARATHON_LINE_CAPS: [u32; 2] = [150, 200];
+
+// Gravity stops getting faster past this level
+const MAX_GRAVITY_LEVEL: u32 = 20;
+
+// Falling 20 rows a frame or more is 20G, the piece can't be seen falling
+const TWENTY_G_INTERVAL: f32 = FRAME_TIME / 20.0;
+
+// Last successful action on the current piece, used for T-spin detection
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LastMove {
+    None,
+    Shift,
+    Rotation { kick: usize },
+}
+
+// Player handling settings, in milliseconds like GameOptions stores them
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Handling {
+    pub das_delay: f32,
+    pub arr_delay: f32,
+    pub soft_drop_factor: f32,
+    pub instant_soft_drop: bool,
+}
+
+impl Default for Handling {
+    fn default() -> Self {
+        Self {
+            das_delay: 150.0,
+            arr_delay: 50.0,
+            soft_drop_factor: 20.0,
+            instant_soft_drop: false,
+        }
+    }
+}
+
+// Headless game simulation. Inputs come in as GameAction presses and releases,
+// time only moves forward through `step`/`tick`, and nothing here touches egui.
+pub struct Engine {
+    pub board: Board,
+    pub queue: Queue,
+    pub hold_queue: HoldQueue,
+    pub current_piece: Option<Piece>,
+    handling: Handling,
+    mode: GameMode,
+    // Lines that end a Marathon, None plays forever
+    line_cap: Option<u32>,
+    level: u32,
+    // TGM style level for Master, counts pieces and lines
+    master_level: u32,
+    combo: u32,
+    score_manager: ScoreManager,
+    lines_cleared: u32,
+    game_over: bool,
+    completed: bool,
+    frame: u64,
+    // Frames left before a timed mode ends
+    countdown: Option<u64>,
+    // Frames left before the next piece spawns (ARE plus line clear delay)
+    spawn_delay: u32,
+    garbage_rng: StdRng,
+    last_garbage_hole: Option<usize>,
+    // Frames until Survival pushes in the next garbage row
+    garbage_timer: u32,
+    garbage_rows_risen: u32,
+    // Versus garbage waiting to rise, one entry per attack received
+    incoming_garbage: Vec<u32>,
+    // Attack built up since the opponent last collected it
+    outgoing_attack: u32,
+    frame_accumulator: f32,
+    drop_timer: f32,
+    drop_interval: f32,
+    current_game_stats: GameStats,
+    lock_delay_timer: f32,
+    lock_delay_duration: f32,
+    piece_on_ground: bool,
+    soft_drop_held: bool,
+    last_move: LastMove,
+    // Moves and rotations pressed for the current piece
+    piece_inputs: u32,
+    last_finesse: Option<FinesseResult>,
+    // Opener being trained and the grade of each placement so far
+    opener: Option<Opener>,
+    opener_grades: Vec<PlacementGrade>,
+    left_held: bool,
+    right_held: bool,
+    left_das_timer: f32,
+    right_das_timer: f32,
+    replay: Replay,
+}
+
+impl Engine {
+    pub fn new(handling: Handling) -> Self {
+        Self::with_seed(handling, rand::random())
+    }
+
+    // Replaying the same inputs on the same seed reproduces the game exactly
+    pub fn with_seed(handling: Handling, seed: u64) -> Self {
+        Self::with_queue(handling, Queue::with_seed(seed))
+    }
+
+    pub fn with_queue(handling: Handling, queue: Queue) -> Self {
+        Self::with_mode(handling, queue, GameMode::Marathon)
+    }
+
+    pub fn with_mode(handling: Handling, queue: Queue, mode: GameMode) -> Self {
+        let mut replay = Replay::new(queue.get_seed(), queue.get_randomizer_kind(), handling, mode);
+        replay.preset_queue = queue.get_preset().to_vec();
+        let garbage_rng = StdRng::seed_from_u64(queue.get_seed() ^ GARBAGE_SEED_SALT);
+        let mut engine = Self {
+            board: Board::new(),
+            queue,
+            hold_queue: HoldQueue::new(),
+            current_piece: None,
+            handling,
+            mode,
+            line_cap: None,
+            level: 1,
+            master_level: 0,
+            combo: 0,
+            score_manager: ScoreManager::new(),
+            lines_cleared: 0,
+            game_over: false,
+            completed: false,
+            frame: 0,
+            countdown: match mode {
+                GameMode::Ultra => Some(ULTRA_FRAMES),
+                _ => None,
+            },
+            spawn_delay: 0,
+            garbage_rng,
+            last_garbage_hole: None,
+            garbage_timer: SURVIVAL_START_INTERVAL,
+            garbage_rows_risen: 0,
+            incoming_garbage: Vec::new(),
+            outgoing_attack: 0,
+            frame_accumulator: 0.0,
+            drop_timer: 0.0,
+            drop_interval: 1.0,
+            current_game_stats: GameStats::new(),
+            lock_delay_timer: 0.0,
+            lock_delay_duration: 1.0, // 1000ms at level 1
+            piece_on_ground: false,
+            soft_drop_held: false,
+            last_move: LastMove::None,
+            piece_inputs: 0,
+            last_finesse: None,
+            opener: None,
+            opener_grades: Vec::new(),
+            left_held: false,
+            right_held: false,
+            left_das_timer: 0.0,
+            right_das_timer: 0.0,
+            replay,
+        };
+
+        engine.drop_interval = engine.calculate_drop_interval();
+        engine.lock_delay_duration = engine.calculate_lock_delay();
+        if mode == GameMode::Dig {
+            for _ in 0..DIG_GARBAGE_ROWS {
+                engine.add_garbage_row();
+            }
+        }
+        if mode == GameMode::PerfectClear {
+            engine.queue.set_length(PC_PREVIEW);
+        }
+        engine.queue.fill();
+        engine.spawn_next_piece();
+
+        engine
+    }
+
+    // End a Marathon after `line_cap` lines
+    pub fn with_line_cap(mut self, line_cap: Option<u32>) -> Self {
+        self.line_cap = line_cap;
+        self.replay.line_cap = line_cap;
+        self
+    }
+
+    // Start from prepared cells instead of an empty board. Cells in the way
+    // of the first piece top out straight away, like a blocked spawn does.
+    pub fn with_starting_board(mut self, cells: Option<Vec<u8>>) -> Self {
+        if let Some(cells) = &cells {
+            let cell_size = self.board.cell_size;
+            self.board = Board::from_ids(cells);
+            self.board.cell_size = cell_size;
+            if self.current_piece.as_ref().is_some_and(|piece| !self.board.is_valid_position(&piece.get_blocks())) {
+                println!("Game Over! No space for new piece.");
+                self.current_piece = None;
+                self.game_over = true;
+            }
+        }
+        self.replay.starting_board = cells;
+        self
+    }
+
+    // Start with a piece already in hold
+    pub fn with_starting_hold(mut self, name: Option<String>) -> Self {
+        self.hold_queue.held_piece = name.as_deref().map(Piece::create_piece);
+        self.replay.starting_hold = name;
+        self
+    }
+
+    // Grade each placement against the opener's steps, in order
+    pub fn with_opener(mut self, opener: Option<Opener>) -> Self {
+        self.opener = opener;
+        self.replay.opener = opener;
+        self
+    }
+
+    pub fn set_handling(&mut self, handling: Handling) {
+        if handling != self.handling {
+            self.replay.record(self.frame, ReplayEvent::SetHandling(handling));
+            self.handling = handling;
+        }
+    }
+
+    pub fn press(&mut self, action: GameAction) {
+        if self.game_over {
+            return;
+        }
+        self.replay.record(self.frame, ReplayEvent::Press(action));
+        self.current_game_stats.key_presses += 1;
+
+        // Every press counts towards finesse, even one that can't move the piece
+        if self.current_piece.is_some()
+            && matches!(action, GameAction::MoveLeft | GameAction::MoveRight | GameAction::RotateCW | GameAction::RotateCCW)
+        {
+            self.piece_inputs += 1;
+        }
+
+        match action {
+            GameAction::MoveLeft => {
+                // Key just pressed - immediate movement
+                self.shift_piece(-1);
+                self.left_held = true;
+                self.left_das_timer = 0.0;
+            }
+            GameAction::MoveRight => {
+                self.shift_piece(1);
+                self.right_held = true;
+                self.right_das_timer = 0.0;
+            }
+            GameAction::SoftDrop => self.soft_drop_held = true,
+            // Nothing to drop or hold while waiting for the next piece
+            GameAction::HardDrop if self.current_piece.is_some() => self.hard_drop(),
+            // A refused hold leaves the piece, its last move and its inputs alone
+            GameAction::HoldPiece if self.current_piece.is_some() && self.hold_queue.can_hold() => {
+                self.hold_queue.hold_piece(&mut self.current_piece, &mut self.queue);
+                self.last_move = LastMove::None;
+                self.piece_inputs = 0;
+            }
+            GameAction::HardDrop | GameAction::HoldPiece => {}
+            GameAction::RotateCW => self.rotate_piece(true),
+            GameAction::RotateCCW => self.rotate_piece(false),
+            // Pausing and restarting are up to whoever drives the engine
+            GameAction::RestartGame | GameAction::PauseGame | GameAction::ResumeGame => {}
+        }
+    }
+
+    pub fn release(&mut self, action: GameAction) {
+        // Only releases of held inputs change anything worth recording
+        let held = match action {
+            GameAction::MoveLeft => self.left_held,
+            GameAction::MoveRight => self.right_held,
+            GameAction::SoftDrop => self.soft_drop_held,
+            _ => false,
+        };
+        if !held {
+            return;
+        }
+        self.replay.record(self.frame, ReplayEvent::Release(action));
+
+        match action {
+            GameAction::MoveLeft => {
+                self.left_held = false;
+                self.left_das_timer = 0.0;
+            }
+            GameAction::MoveRight => {
+                self.right_held = false;
+                self.right_das_timer = 0.0;
+            }
+            GameAction::SoftDrop => self.soft_drop_held = false,
+            _ => {}
+        }
+    }
+
+    // Queue garbage sent by an opponent, it rises after the next piece that
+    // doesn't clear lines unless it gets cancelled first
+    pub fn receive_garbage(&mut self, lines: u32) {
+        if self.game_over || lines == 0 {
+            return;
+        }
+        self.replay.record(self.frame, ReplayEvent::Garbage(lines));
+        self.incoming_garbage.push(lines);
+    }
+
+    // Collect the attack to send to the opponent
+    pub fn take_attack(&mut self) -> u32 {
+        std::mem::take(&mut self.outgoing_attack)
+    }
+
+    pub fn get_incoming_garbage(&self) -> u32 {
+        self.incoming_garbage.iter().sum()
+    }
+
+    pub fn release_all(&mut self) {
+        self.release(GameAction::MoveLeft);
+        self.release(GameAction::MoveRight);
+        self.release(GameAction::SoftDrop);
+    }
+
+    // Advance by real elapsed time, running as many whole frames as have built up
+    pub fn step(&mut self, delta_time: f32) {
+        self.frame_accumulator += delta_time;
+        while self.frame_accumulator >= FRAME_TIME && !self.game_over {
+            self.frame_accumulator -= FRAME_TIME;
+            self.tick();
+        }
+    }
+
+    // Advance exactly one frame
+    pub fn tick(&mut self) {
+        if self.game_over {
+            return;
+        }
+
+        // Count down ARE, DAS keeps charging in the meantime
+        if self.spawn_delay > 0 {
+            self.spawn_delay -= 1;
+            if self.spawn_delay == 0 {
+                self.spawn_next_piece();
+            }
+        }
+
+        // Handle left/right movement with DAS (Delayed Auto Shift)
+        if self.left_held {
+            self.left_das_timer += FRAME_TIME;
+            self.left_das_timer = self.apply_auto_shift(-1, self.left_das_timer);
+        }
+        if self.right_held {
+            self.right_das_timer += FRAME_TIME;
+            self.right_das_timer = self.apply_auto_shift(1, self.right_das_timer);
+        }
+
+        // Instant soft drop sends the piece to the floor without locking it
+        if self.soft_drop_held && self.handling.instant_soft_drop {
+            self.sonic_drop();
+        }
+
+        // Soft drop multiplies gravity by the soft drop factor
+        let interval = if self.soft_drop_held {
+            self.drop_interval / self.handling.soft_drop_factor.max(1.0)
+        } else {
+            self.drop_interval
+        };
+
+        // Update drop timer
+        self.drop_timer += FRAME_TIME;
+
+        // Check if it's time to drop the piece, several rows may fall in one frame
+        while self.drop_timer >= interval {
+            self.drop_timer -= interval;
+            if !self.apply_gravity() {
+                self.drop_timer = 0.0;
+                break;
+            }
+            if self.soft_drop_held {
+                self.score_manager.drop(1, 1);
+            }
+        }
+
+        // Handle lock delay when piece is on ground
+        if self.piece_on_ground {
+            self.lock_delay_timer += FRAME_TIME;
+
+            if self.lock_delay_timer >= self.lock_delay_duration {
+                self.lock_piece();
+                self.piece_on_ground = false;
+                self.lock_delay_timer = 0.0;
+            }
+        }
+
+        // Survival garbage keeps rising whatever the player is doing
+        if self.mode == GameMode::Survival {
+            self.garbage_timer -= 1;
+            if self.garbage_timer == 0 {
+                self.rise_garbage();
+                self.garbage_timer = self.calculate_garbage_interval();
+            }
+        }
+
+        self.frame += 1;
+
+        // Timed modes end cleanly when the clock runs out, whatever the piece is doing
+        if let Some(remaining) = &mut self.countdown {
+            *remaining -= 1;
+            if *remaining == 0 {
+                self.finish();
+            }
+        }
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.game_over
+    }
+
+    // Whether the game ended by reaching the mode's goal rather than topping out
+    pub fn is_completed(&self) -> bool {
+        self.completed
+    }
+
+    pub fn get_mode(&self) -> GameMode {
+        self.mode
+    }
+
+    // Garbage rows still on the board
+    pub fn get_garbage_remaining(&self) -> usize {
+        self.board.count_garbage_rows()
+    }
+
+    // Time until the next Survival garbage row, in milliseconds
+    pub fn get_garbage_countdown_ms(&self) -> Option<u64> {
+        (self.mode == GameMode::Survival).then(|| self.garbage_timer as u64 * 1000 / 60)
+    }
+
+    pub fn get_pieces_placed(&self) -> u32 {
+        self.current_game_stats.pieces_placed
+    }
+
+    pub fn get_finesse_faults(&self) -> u32 {
+        self.current_game_stats.finesse_faults
+    }
+
+    // Finesse of the last piece placed, if it could be judged
+    pub fn get_last_finesse(&self) -> Option<FinesseResult> {
+        self.last_finesse
+    }
+
+    pub fn get_line_cap(&self) -> Option<u32> {
+        self.line_cap
+    }
+
+    // In-game time in milliseconds, counted in engine frames
+    pub fn get_time_ms(&self) -> u64 {
+        self.frame * 1000 / 60
+    }
+
+    // Time left in a timed mode, in milliseconds
+    pub fn get_time_remaining_ms(&self) -> Option<u64> {
+        self.countdown.map(|frames| frames * 1000 / 60)
+    }
+
+    // Number of frames simulated so far
+    pub fn get_frame(&self) -> u64 {
+        self.frame
+    }
+
+    // Pieces placed per second of in-game time
+    pub fn get_pps(&self) -> f32 {
+        per_minute(self.current_game_stats.pieces_placed, self.frame) / 60.0
+    }
+
+    // Key presses per piece placed
+    pub fn get_kpp(&self) -> f32 {
+        match self.current_game_stats.pieces_placed {
+            0 => 0.0,
+            pieces => self.current_game_stats.key_presses as f32 / pieces as f32,
+        }
+    }
+
+    pub fn get_lines_per_minute(&self) -> f32 {
+        per_minute(self.lines_cleared, self.frame)
+    }
+
+    // Garbage lines sent per minute, counted before cancelling
+    pub fn get_attack_per_minute(&self) -> f32 {
+        per_minute(self.current_game_stats.attack_sent, self.frame)
+    }
+
+    pub fn get_score(&self) -> u32 {
+        self.score_manager.get_score()
+    }
+
+    pub fn get_level(&self) -> u32 {
+        match self.mode {
+            GameMode::Master => self.master_level,
+            _ => self.level,
+        }
+    }
+
+    pub fn get_combo(&self) -> u32 {
+        self.combo
+    }
+
+    // Every T-spin so far, minis and ones that cleared nothing included
+    pub fn get_t_spins(&self) -> u32 {
+        let stats = &self.current_game_stats;
+        stats.t_spin_minis + stats.t_spin_zeros + stats.t_spin_singles + stats.t_spin_doubles + stats.t_spin_triples
+    }
+
+    pub fn get_perfect_clears(&self) -> u32 {
+        self.current_game_stats.perfect_clears
+    }
+
+    pub fn get_opener(&self) -> Option<Opener> {
+        self.opener
+    }
+
+    pub fn get_opener_grades(&self) -> &[PlacementGrade] {
+        &self.opener_grades
+    }
+
+    // The opener's next placement, None once the run is over
+    pub fn get_opener_step(&self) -> Option<OpenerStep> {
+        if self.game_over {
+            return None;
+        }
+        self.opener?.steps().get(self.opener_grades.len()).copied()
+    }
+
+    // Cells the opener's next placement covers on the current board
+    pub fn get_opener_target(&self) -> Option<Vec<(i32, i32)>> {
+        self.get_opener_step()?.target_blocks(&self.board)
+    }
+
+    // Rows the stack has to stay inside for a perfect clear in PC practice
+    pub fn get_pc_height(&self) -> usize {
+        PC_HEIGHT.saturating_sub(self.lines_cleared as usize)
+    }
+
+    pub fn get_back_to_back_chain(&self) -> u32 {
+        self.score_manager.get_back_to_back_chain()
+    }
+
+    pub fn get_lines_cleared(&self) -> u32 {
+        self.lines_cleared
+    }
+
+    // Inputs recorded so far, enough to re-simulate this game from the start
+    pub fn get_replay(&self) -> Replay {
+        self.replay.clone()
+    }
+
+    pub fn get_game_stats(&self) -> GameStats {
+        let mut stats = self.current_game_stats.clone();
+        stats.seed = self.queue.get_seed();
+        stats.randomizer = self.queue.get_randomizer_kind();
+        stats.mode = self.mode;
+        // Survival has no goal, the time survived is what counts
+        stats.finish_time_ms = (self.completed || self.mode == GameMode::Survival).then(|| self.get_time_ms());
+        stats.score = self.score_manager.get_score();
+        stats.lines_cleared = self.lines_cleared;
+        stats.level_reached = self.get_level();
+        stats.play_time_ms = self.get_time_ms();
+        stats.duration_seconds = stats.play_time_ms / 1000;
+        stats.pps = self.get_pps();
+        stats.kpp = self.get_kpp();
+        stats.lines_per_minute = self.get_lines_per_minute();
+        stats.attack_per_minute = self.get_attack_per_minute();
+        stats
+    }
+
+    // Where the current piece would land if hard dropped
+    pub fn get_ghost_piece(&self) -> Option<Piece> {
+        let mut ghost_piece = self.current_piece.clone()?;
+
+        // Drop ghost piece until it collides
+        loop {
+            ghost_piece.move_down();
+            if !self.board.is_valid_position(&ghost_piece.get_blocks()) {
+                ghost_piece.move_up(); // Undo last move
+                break;
+            }
+        }
+        Some(ghost_piece)
+    }
+
+    fn rotate_piece(&mut self, clockwise: bool) {
+        if let Some(piece) = &mut self.current_piece {
+            if let Some(kick) = piece.rotate(clockwise, &self.board) {
+                self.last_move = LastMove::Rotation { kick };
+                self.reset_lock_delay();
+            }
+        }
+    }
+
+    // Auto-repeat a held direction once DAS has charged, returning the updated DAS timer
+    fn apply_auto_shift(&mut self, direction: i32, das_timer: f32) -> f32 {
+        // Master sets its own DAS per section
+        let das_delay = match self.mode {
+            GameMode::Master => master::timing(self.master_level).das as f32 * FRAME_TIME,
+            _ => self.handling.das_delay / 1000.0,
+        };
+        let arr_delay = self.handling.arr_delay / 1000.0;
+
+        if das_timer < das_delay {
+            return das_timer;
+        }
+
+        // ARR of 0 shifts the piece straight to the wall
+        if arr_delay <= 0.0 {
+            while self.shift_piece(direction) {}
+            return das_delay;
+        }
+
+        // DAS delay passed, now auto-repeat at ARR rate
+        let time_since_das = das_timer - das_delay;
+        let moves = (time_since_das / arr_delay) as i32;
+        for _ in 0..moves {
+            if !self.shift_piece(direction) {
+                break;
+            }
+        }
+        das_delay + (time_since_das % arr_delay)
+    }
+
+    // Move the current piece one column (-1 left, 1 right), returning whether it moved
+    fn shift_piece(&mut self, direction: i32) -> bool {
+        if let Some(piece) = &mut self.current_piece {
+            if direction < 0 { piece.move_left() } else { piece.move_right() }
+            if self.board.is_valid_position(&piece.get_blocks()) {
+                self.last_move = LastMove::Shift;
+                self.reset_lock_delay();
+                return true;
+            }
+            // Undo
+            if direction < 0 { piece.move_right() } else { piece.move_left() }
+        }
+        false
+    }
+
+    fn hard_drop(&mut self) {
+        if let Some(piece) = &mut self.current_piece {
+            let mut cells_dropped = 0;
+
+            // Move piece down until it collides
+            loop {
+                piece.move_down();
+                if !self.board.is_valid_position(&piece.get_blocks()) {
+                    piece.move_up(); // Undo last move
+                    break;
+                }
+                cells_dropped += 1;
+            }
+
+            // Award hard drop points (2 points per cell)
+            if cells_dropped > 0 {
+                self.score_manager.drop(2, cells_dropped);
+                self.last_move = LastMove::Shift;
+            }
+        }
+
+        // Lock the piece immediately (bypass lock delay)
+        self.piece_on_ground = false;
+        self.lock_delay_timer = 0.0;
+        self.lock_piece();
+    }
+
+    fn reset_lock_delay(&mut self) {
+        // Reset lock delay timer when piece is moved/rotated successfully
+        if self.piece_on_ground {
+            self.lock_delay_timer = 0.0;
+        }
+    }
+
+    fn lock_piece(&mut self) {
+        // Check T-spin corners before the piece joins the stack
+        let t_spin = self.detect_t_spin();
+        self.check_finesse();
+        self.grade_opener_placement();
+
+        if let Some(piece) = &self.current_piece {
+            let blocks = piece.get_blocks();
+            let id = piece.get_id();
+
+            // Place piece blocks permanently on the board
+            for (row, col) in &blocks {
+                if *row >= 0 && *col >= 0 {
+                    self.board.set_cell(*row as usize, *col as usize, id);
+                }
+            }
+
+            // Track pieces placed
+            self.current_game_stats.pieces_placed += 1;
+        }
+
+        // Clear completed lines
+        let cleared = self.board.clear_lines();
+
+        // Track T-spins by type
+        match t_spin {
+            TSpin::Mini => self.current_game_stats.t_spin_minis += 1,
+            TSpin::Full => match cleared {
+                0 => self.current_game_stats.t_spin_zeros += 1,
+                1 => self.current_game_stats.t_spin_singles += 1,
+                2 => self.current_game_stats.t_spin_doubles += 1,
+                _ => self.current_game_stats.t_spin_triples += 1,
+            },
+            TSpin::None => {}
+        }
+
+        if cleared > 0 {
+            // Track line clears by type
+            match cleared {
+                1 => self.current_game_stats.singles += 1,
+                2 => self.current_game_stats.doubles += 1,
+                3 => self.current_game_stats.triples += 1,
+                4 => self.current_game_stats.quadruples += 1,
+                _ => {}
+            }
+
+            // Increment combo
+            self.combo += 1;
+
+            // Track max combo
+            if self.combo > self.current_game_stats.max_combo {
+                self.current_game_stats.max_combo = self.combo;
+            }
+
+            // Use score manager to handle scoring
+            let perfect_clear = self.board.is_empty();
+            let clear = self.score_manager.lines_cleared(cleared, self.level, self.combo, t_spin, perfect_clear);
+            self.lines_cleared += cleared;
+
+            if clear.perfect_clear {
+                self.current_game_stats.perfect_clears += 1;
+            }
+
+            if clear.back_to_back {
+                self.current_game_stats.back_to_backs += 1;
+                self.current_game_stats.longest_back_to_back =
+                    self.current_game_stats.longest_back_to_back.max(clear.back_to_back_chain);
+            }
+
+            let attack = attack::attack_lines(&clear);
+            self.current_game_stats.attack_sent += attack;
+            self.send_attack(attack);
+
+            // Check for level up
+            self.update_level();
+
+            println!("Cleared {} line(s)! Combo: {} Total lines: {}", cleared, self.combo, self.lines_cleared);
+            println!("Score: {}", self.score_manager.get_score());
+        } else {
+            // T-spins still score without clearing lines
+            if t_spin != TSpin::None {
+                self.score_manager.lines_cleared(0, self.level, 0, t_spin, false);
+            }
+
+            // Reset combo if no lines cleared
+            self.combo = 0;
+        }
+
+        // Reset hold permission after locking
+        self.hold_queue.reset_hold();
+
+        if self.mode == GameMode::Master {
+            self.advance_master_level(cleared);
+        }
+
+        // Garbage that wasn't cancelled rises once a piece locks without clearing
+        if cleared == 0 && !self.raise_incoming_garbage() {
+            self.current_piece = None;
+            self.game_over = true;
+            return;
+        }
+
+        // The opener can't be followed any further once a piece misses its spot
+        if self.opener_grades.last() == Some(&PlacementGrade::Miss) {
+            self.current_piece = None;
+            self.game_over = true;
+            return;
+        }
+
+        if self.is_goal_reached() {
+            self.finish();
+            return;
+        }
+
+        // PC practice is lost once the stack grows out of the rows left to clear
+        if self.mode == GameMode::PerfectClear && self.board.get_stack_height() > self.get_pc_height() {
+            self.current_piece = None;
+            self.game_over = true;
+            return;
+        }
+
+        // Spawn next piece, after ARE and line clear delay in modes that have them
+        self.current_piece = None;
+        self.spawn_delay = self.calculate_spawn_delay(cleared);
+        if self.spawn_delay == 0 {
+            self.spawn_next_piece();
+        }
+    }
+
+    // Compare the inputs used on the locking piece with the fewest possible.
+    // Pieces tucked or spun under an overhang can't be judged and are skipped.
+    fn check_finesse(&mut self) {
+        let Some(piece) = &self.current_piece else {
+            return;
+        };
+
+        let mut probe = piece.clone();
+        while probe.get_position().0 > 0 {
+            probe.move_up();
+            if !self.board.is_valid_position(&probe.get_blocks()) {
+                self.last_finesse = None;
+                return;
+            }
+        }
+
+        self.last_finesse = finesse::minimum_inputs(piece).map(|minimum| FinesseResult {
+            inputs: self.piece_inputs,
+            minimum,
+        });
+        if self.last_finesse.is_some_and(|result| result.is_fault()) {
+            self.current_game_stats.finesse_faults += 1;
+        }
+    }
+
+    // Grade the locking piece against the opener's next placement, on the
+    // board as it is before the piece joins it
+    fn grade_opener_placement(&mut self) {
+        let (Some(opener), Some(piece)) = (self.opener, &self.current_piece) else {
+            return;
+        };
+        let Some(step) = opener.steps().get(self.opener_grades.len()) else {
+            return;
+        };
+
+        let target = step.target_blocks(&self.board);
+        let grade = PlacementGrade::grade(&piece.get_blocks(), target.as_deref(), self.last_finesse);
+        self.opener_grades.push(grade);
+    }
+
+    // Applies the 3-corner rule to a T piece whose last move was a rotation.
+    // Both front corners filled is a full T-spin, otherwise a mini unless the
+    // last SRS kick (the 1x2 "TST" kick) was used.
+    fn detect_t_spin(&self) -> TSpin {
+        let piece = match &self.current_piece {
+            Some(piece) if piece.get_name() == "T" => piece,
+            _ => return TSpin::None,
+        };
+        let kick = match self.last_move {
+            LastMove::Rotation { kick } => kick,
+            _ => return TSpin::None,
+        };
+
+        let (row, col) = piece.get_position();
+        let top_left = self.board.is_occupied(row, col);
+        let top_right = self.board.is_occupied(row, col + 2);
+        let bottom_left = self.board.is_occupied(row + 2, col);
+        let bottom_right = self.board.is_occupied(row + 2, col + 2);
+
+        let corners = [top_left, top_right, bottom_left, bottom_right]
+            .iter()
+            .filter(|c| **c)
+            .count();
+        if corners < 3 {
+            return TSpin::None;
+        }
+
+        // Front corners are the two on the side the T is pointing to
+        let (front_a, front_b) = match piece.get_rotation() {
+            0 => (top_left, top_right),
+            1 => (top_right, bottom_right),
+            2 => (bottom_left, bottom_right),
+            _ => (top_left, bottom_left),
+        };
+
+        if (front_a && front_b) || kick == 4 {
+            TSpin::Full
+        } else {
+            TSpin::Mini
+        }
+    }
+
+    fn is_goal_reached(&self) -> bool {
+        match self.mode {
+            GameMode::Marathon => self.line_cap.is_some_and(|cap| self.lines_cleared >= cap),
+            GameMode::Sprint => self.lines_cleared >= SPRINT_LINES,
+            GameMode::Ultra | GameMode::Survival | GameMode::Versus | GameMode::Puzzle => false,
+            GameMode::Master => self.master_level >= MASTER_MAX_LEVEL,
+            GameMode::Dig => self.board.count_garbage_rows() == 0,
+            GameMode::PerfectClear => self.current_game_stats.perfect_clears > 0,
+            GameMode::Opener => self.opener.is_some_and(|opener| self.opener_grades.len() >= opener.steps().len()),
+        }
+    }
+
+    // Random hole column that never lines up with the previous one
+    fn next_garbage_hole(&mut self) -> usize {
+        let width = Board::get_width();
+        let hole = match self.last_garbage_hole {
+            Some(last) => (last + self.garbage_rng.gen_range(1..width)) % width,
+            None => self.garbage_rng.gen_range(0..width),
+        };
+        self.last_garbage_hole = Some(hole);
+        hole
+    }
+
+    // Push a garbage row in from the bottom. Returns false if the stack was
+    // pushed out of the top.
+    fn add_garbage_row(&mut self) -> bool {
+        let hole = self.next_garbage_hole();
+        self.board.insert_garbage_row(hole)
+    }
+
+    // Attacks cancel incoming garbage first, oldest first, the rest goes out
+    fn send_attack(&mut self, mut attack: u32) {
+        while attack > 0 && !self.incoming_garbage.is_empty() {
+            let cancelled = attack.min(self.incoming_garbage[0]);
+            self.incoming_garbage[0] -= cancelled;
+            attack -= cancelled;
+            if self.incoming_garbage[0] == 0 {
+                self.incoming_garbage.remove(0);
+            }
+        }
+        self.outgoing_attack += attack;
+    }
+
+    // Raise all queued garbage, each attack with its own hole column. Returns
+    // false if the stack was pushed out of the top.
+    fn raise_incoming_garbage(&mut self) -> bool {
+        let mut fits = true;
+        for lines in std::mem::take(&mut self.incoming_garbage) {
+            let hole = self.next_garbage_hole();
+            for _ in 0..lines {
+                fits &= self.board.insert_garbage_row(hole);
+            }
+        }
+        fits
+    }
+
+    // Raise the stack by a garbage row. The falling piece is carried up with it,
+    // and it's a top out if the stack leaves the board or the piece has no room.
+    fn rise_garbage(&mut self) {
+        let fits = self.add_garbage_row();
+        self.garbage_rows_risen += 1;
+
+        let mut piece_fits = true;
+        if let Some(piece) = &mut self.current_piece {
+            if !self.board.is_valid_position(&piece.get_blocks()) {
+                piece.move_up();
+                piece_fits = self.board.is_valid_position(&piece.get_blocks());
+            }
+        }
+
+        if !fits || !piece_fits {
+            self.current_piece = None;
+            self.game_over = true;
+        }
+    }
+
+    fn calculate_garbage_interval(&self) -> u32 {
+        let interval = SURVIVAL_START_INTERVAL as f32 * SURVIVAL_SPEEDUP.powi(self.garbage_rows_risen as i32);
+        (interval as u32).max(SURVIVAL_MIN_INTERVAL)
+    }
+
+    // End the game as completed instead of topped out
+    fn finish(&mut self) {
+        self.current_piece = None;
+        self.piece_on_ground = false;
+        self.completed = true;
+        self.game_over = true;
+    }
+
+    fn spawn_next_piece(&mut self) {
+        // Every new piece counts a Master level, except to leave a section
+        if self.mode == GameMode::Master
+            && self.current_game_stats.pieces_placed > 0
+            && self.master_level < master::section_stop(self.master_level)
+        {
+            self.master_level += 1;
+        }
+
+        self.last_move = LastMove::None;
+        self.piece_inputs = 0;
+        self.current_piece = self.queue.get_next_piece();
+
+        if let Some(piece) = &self.current_piece {
+            // Check if the new piece collides immediately (game over condition)
+            if !self.board.is_valid_position(&piece.get_blocks()) {
+                println!("Game Over! No space for new piece.");
+                self.current_piece = None; // Clear the piece that couldn't spawn
+                self.game_over = true;
+                return;
+            }
+        }
+
+        // At 20G pieces spawn already resting on the stack
+        if self.is_twenty_g() {
+            while self.apply_gravity() {}
+        }
+    }
+
+    fn is_twenty_g(&self) -> bool {
+        self.drop_interval <= TWENTY_G_INTERVAL
+    }
+
+    // Move the piece down one row, returning whether it moved
+    fn apply_gravity(&mut self) -> bool {
+        if let Some(piece) = &mut self.current_piece {
+            piece.move_down();
+
+            // Check if the move is valid
+            if !self.board.is_valid_position(&piece.get_blocks()) {
+                piece.move_up(); // Undo the move
+                self.piece_on_ground = true; // Piece is now resting on something
+            } else {
+                // Piece successfully moved down, reset lock delay
+                self.last_move = LastMove::Shift;
+                self.piece_on_ground = false;
+                self.lock_delay_timer = 0.0;
+                return true;
+            }
+        }
+        false
+    }
+
+    // Drop the piece to the floor like a hard drop, but leave it to the lock delay
+    fn sonic_drop(&mut self) {
+        let mut cells_dropped = 0;
+        while self.apply_gravity() {
+            cells_dropped += 1;
+        }
+        if cells_dropped > 0 {
+            self.score_manager.drop(1, cells_dropped);
+        }
+    }
+
+    // Master levels go up with every line cleared and can cross section stops
+    fn advance_master_level(&mut self, cleared: u32) {
+        self.master_level = (self.master_level + cleared).min(MASTER_MAX_LEVEL);
+        self.lock_delay_duration = self.calculate_lock_delay();
+    }
+
+    // Frames to wait before spawning the next piece
+    fn calculate_spawn_delay(&self, cleared: u32) -> u32 {
+        match self.mode {
+            GameMode::Master => {
+                let timing = master::timing(self.master_level);
+                if cleared > 0 { timing.are + timing.line_clear } else { timing.are }
+            }
+            _ => 0,
+        }
+    }
+
+    fn calculate_drop_interval(&self) -> f32 {
+        if self.mode == GameMode::Master {
+            return TWENTY_G_INTERVAL;
+        }
+
+        // Guideline gravity: seconds per row = (0.8 - ((level - 1) * 0.007))^(level - 1).
+        // Goes from 1s at level 1 past 20G (under a frame per 20 rows) by level 19.
+        let level = self.level.min(MAX_GRAVITY_LEVEL) as f32;
+        (0.8 - (level - 1.0) * 0.007).powf(level - 1.0)
+    }
+
+    fn calculate_lock_delay(&self) -> f32 {
+        if self.mode == GameMode::Master {
+            return master::timing(self.master_level).lock as f32 * FRAME_TIME;
+        }
+
+        // Decrease lock delay as level increases
+        // Start at 500ms, reduce to minimum 100ms at high levels
+        let base_delay = 0.5;
+        let level_multiplier = 0.92_f32.powi(self.level as i32 - 1);
+        (base_delay * level_multiplier).max(0.1) // Minimum 100ms
+    }
+
+    fn update_level(&mut self) {
+        // Increase level every 10 lines
+        let new_level = (self.lines_cleared / 10) + 1;
+        if new_level != self.level {
+            self.level = new_level;
+            self.drop_interval = self.calculate_drop_interval();
+            self.lock_delay_duration = self.calculate_lock_delay();
+            println!("Level up! Now at level {}", self.level);
+        }
+    }
+}
+
+// Rate of `count` over `frames` of in-game time, paused time never reaches the engine
+fn per_minute(count: u32, frames: u64) -> f32 {
+    if frames == 0 {
+        return 0.0;
+    }
+    count as f32 / (frames as f32 * FRAME_TIME / 60.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classes::board::GARBAGE_ID;
+
+    // Cells for a board whose bottom rows are `rows`, '#' filled and '.' empty
+    fn board_cells(rows: &[&str]) -> Vec<u8> {
+        let mut cells = vec![0; Board::get_width() * Board::get_height()];
+        let top = Board::get_height() - rows.len();
+        for (i, row) in rows.iter().enumerate() {
+            for (col, cell) in row.chars().enumerate() {
+                if cell == '#' {
+                    cells[(top + i) * Board::get_width() + col] = GARBAGE_ID;
+                }
+            }
+        }
+        cells
+    }
+
+    // Names of the first `count` pieces the engine deals, clearing the board
+    // after every drop so it never tops out
+    fn dealt_pieces(seed: u64, count: usize) -> Vec<String> {
+        let mut engine = Engine::with_seed(Handling::default(), seed);
+        let mut names = Vec::new();
+        for _ in 0..count {
+            names.push(engine.current_piece.as_ref().unwrap().get_name().to_string());
+            engine.press(GameAction::HardDrop);
+            engine.board = Board::new();
+            engine.tick();
+        }
+        names
+    }
+
+    #[test]
+    fn same_seed_deals_the_same_pieces() {
+        let pieces = dealt_pieces(42, 21);
+        assert_eq!(pieces, dealt_pieces(42, 21));
+        assert_ne!(pieces, dealt_pieces(43, 21));
+
+        // Each bag of the default 7-bag holds every piece once
+        for bag in pieces.chunks(7) {
+            let mut bag = bag.to_vec();
+            bag.sort();
+            assert_eq!(bag, ["I", "J", "L", "O", "S", "T", "Z"]);
+        }
+    }
+
+    #[test]
+    fn t_rotated_into_a_covered_slot_is_a_t_spin_double() {
+        let queue = Queue::with_seed(1).with_preset(vec!["T".to_string()]);
+        let mut engine = Engine::with_queue(Handling::default(), queue).with_starting_board(Some(board_cells(&[
+            "...#......",
+            "###...####",
+            "####.#####",
+        ])));
+
+        // Stand the T up beside the slot, then spin it in under the overhang
+        engine.press(GameAction::RotateCW);
+        let piece = engine.current_piece.as_mut().unwrap();
+        for _ in 0..19 {
+            piece.move_down();
+        }
+        assert!(engine.board.is_valid_position(&piece.get_blocks()));
+        engine.press(GameAction::RotateCW);
+        assert_eq!(engine.detect_t_spin(), TSpin::Full);
+
+        engine.press(GameAction::HardDrop);
+        assert_eq!(engine.get_lines_cleared(), 2);
+        assert_eq!(engine.get_game_stats().t_spin_doubles, 1);
+    }
+
+    // Tetris with a vertical I down the well in column 5, after `incoming`
+    // garbage has queued up. A block is left over so it's no perfect clear
+    fn tetris_against(incoming: &[u32]) -> Engine {
+        let queue = Queue::with_seed(1).with_preset(vec!["I".to_string(), "O".to_string()]);
+        let mut engine = Engine::with_mode(Handling::default(), queue, GameMode::Versus).with_starting_board(Some(board_cells(&[
+            "#.........",
+            "#####.####",
+            "#####.####",
+            "#####.####",
+            "#####.####",
+        ])));
+        for lines in incoming {
+            engine.receive_garbage(*lines);
+        }
+        engine.press(GameAction::RotateCW);
+        engine.press(GameAction::HardDrop);
+        assert_eq!(engine.get_lines_cleared(), 4);
+        engine
+    }
+
+    #[test]
+    fn attack_cancels_incoming_garbage_before_it_is_sent() {
+        let cases: [(&[u32], u32, u32); 4] = [
+            (&[], 0, 4),
+            (&[3], 0, 1),
+            (&[2, 2], 0, 0),
+            // Oldest attack first, whatever is left of it stays queued
+            (&[1, 5], 2, 0),
+        ];
+        for (incoming, left, sent) in cases {
+            let mut engine = tetris_against(incoming);
+            assert_eq!(engine.get_incoming_garbage(), left, "{:?}", incoming);
+            assert_eq!(engine.take_attack(), sent, "{:?}", incoming);
+        }
+    }
+
+    #[test]
+    fn uncancelled_garbage_rises_when_a_piece_locks_without_clearing() {
+        let mut engine = tetris_against(&[6]);
+        assert_eq!(engine.get_incoming_garbage(), 2);
+        let rows = engine.board.count_garbage_rows();
+        engine.tick();
+        engine.press(GameAction::HardDrop);
+        assert_eq!(engine.get_incoming_garbage(), 0);
+        assert_eq!(engine.board.count_garbage_rows(), rows + 2);
+    }
+}
@@ -0,0 +1,144 @@
+use super::board::Board;
+use super::piece::Piece;
+use std::collections::{HashSet, VecDeque};
+
+// Inputs spent placing a piece against the fewest that would have done it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FinesseResult {
+    pub inputs: u32,
+    pub minimum: u32,
+}
+
+impl FinesseResult {
+    pub fn is_fault(&self) -> bool {
+        self.inputs > self.minimum
+    }
+}
+
+// Fewest inputs that take a freshly spawned piece of the same type to the
+// columns and orientation `placed` covers. Taps, DAS to the wall and rotations
+// each count as one input, judged on an empty board like finesse usually is.
+pub fn minimum_inputs(placed: &Piece) -> Option<u32> {
+    let board = Board::new();
+    let target = footprint(placed);
+    let start = Piece::create_piece(placed.get_name());
+
+    let mut seen = HashSet::from([state(&start)]);
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((piece, inputs)) = queue.pop_front() {
+        if footprint(&piece) == target {
+            return Some(inputs);
+        }
+        for next in moves(&piece, &board) {
+            if seen.insert(state(&next)) {
+                queue.push_back((next, inputs + 1));
+            }
+        }
+    }
+    None
+}
+
+// Blocks moved to the top row, so positions only differ by column and shape.
+// Orientations that cover the same cells, like the two vertical I's, match.
+fn footprint(piece: &Piece) -> Vec<(i32, i32)> {
+    let blocks = piece.get_blocks();
+    let top = blocks.iter().map(|(row, _)| *row).min().unwrap_or(0);
+    let mut footprint: Vec<_> = blocks.iter().map(|(row, col)| (row - top, *col)).collect();
+    footprint.sort();
+    footprint
+}
+
+fn state(piece: &Piece) -> (u8, (i32, i32)) {
+    (piece.get_rotation(), piece.get_position())
+}
+
+// Everything one input can do: tap either way, DAS to either wall, rotate either way
+fn moves(piece: &Piece, board: &Board) -> Vec<Piece> {
+    let mut moves = Vec::new();
+
+    for direction in [-1, 1] {
+        let mut shifted = piece.clone();
+        let mut steps = 0;
+        loop {
+            if direction < 0 { shifted.move_left() } else { shifted.move_right() }
+            if !board.is_valid_position(&shifted.get_blocks()) {
+                if direction < 0 { shifted.move_right() } else { shifted.move_left() }
+                break;
+            }
+            steps += 1;
+            if steps == 1 {
+                moves.push(shifted.clone());
+            }
+        }
+        if steps > 1 {
+            moves.push(shifted);
+        }
+    }
+
+    for clockwise in [true, false] {
+        let mut rotated = piece.clone();
+        if rotated.rotate(clockwise, board).is_some() {
+            moves.push(rotated);
+        }
+    }
+
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classes::engine::{Engine, Handling};
+    use crate::classes::queue::Queue;
+    use crate::enums::game_actions::GameAction;
+
+    // A freshly spawned piece rotated then pushed against a wall
+    fn placed(name: &str, rotations: usize, direction: i32) -> Piece {
+        let board = Board::new();
+        let mut piece = Piece::create_piece(name);
+        for _ in 0..rotations {
+            piece.rotate(true, &board).unwrap();
+        }
+        loop {
+            if direction < 0 { piece.move_left() } else { piece.move_right() }
+            if !board.is_valid_position(&piece.get_blocks()) {
+                if direction < 0 { piece.move_right() } else { piece.move_left() }
+                return piece;
+            }
+        }
+    }
+
+    #[test]
+    fn walls_take_the_fewest_inputs() {
+        let cases = [
+            ("O to the left wall", placed("O", 0, -1), 1),
+            ("O to the right wall", placed("O", 0, 1), 1),
+            ("T flat to the left wall", placed("T", 0, -1), 1),
+            ("I vertical in column 0", placed("I", 1, -1), 2),
+        ];
+        for (case, piece, expected) in cases {
+            assert_eq!(minimum_inputs(&piece), Some(expected), "{}", case);
+        }
+        assert_eq!(placed("I", 1, -1).get_blocks().iter().map(|(_, col)| *col).max(), Some(0));
+    }
+
+    #[test]
+    fn extra_inputs_count_as_a_fault() {
+        let queue = Queue::with_seed(1).with_preset(vec!["O".to_string(), "O".to_string()]);
+        let mut engine = Engine::with_queue(Handling::default(), queue);
+
+        // Four taps to the wall where holding left once would do
+        for _ in 0..4 {
+            engine.press(GameAction::MoveLeft);
+            engine.release(GameAction::MoveLeft);
+        }
+        engine.press(GameAction::HardDrop);
+        assert_eq!(engine.get_last_finesse(), Some(FinesseResult { inputs: 4, minimum: 1 }));
+        assert_eq!(engine.get_finesse_faults(), 1);
+
+        // Dropping straight down from spawn is already the fewest
+        engine.press(GameAction::HardDrop);
+        assert_eq!(engine.get_last_finesse(), Some(FinesseResult { inputs: 0, minimum: 0 }));
+        assert_eq!(engine.get_finesse_faults(), 1);
+    }
+}
@@ -1,109 +1,83 @@
+use super::engine::{Engine, DIG_GARBAGE_ROWS, SPRINT_LINES};
+use super::master;
 use super::board::Board;
-use super::piece::Piece;
 use super::queue::Queue;
-use super::score_manager::ScoreManager;
-use super::hold::HoldQueue;
 use super::player::GameStats;
-use crate::{classes::game_options::GameOptions, enums::{game_actions::GameAction, states::GameState}};
-use std::time::{SystemTime, UNIX_EPOCH};
-
+use super::perfect_clear::{PcHint, PcSolver, PC_HEIGHT, PC_PREVIEW};
+use super::puzzle::Puzzle;
+use super::opener::PlacementGrade;
+use super::randomizer::RandomizerKind;
+use crate::{classes::game_options::GameOptions, enums::{game_actions::GameAction, game_modes::GameMode, states::GameState}};
+use std::collections::HashSet;
 
+// Actions forwarded to the engine, the rest are handled by the app
+const ENGINE_ACTIONS: [GameAction; 7] = [
+    GameAction::MoveLeft,
+    GameAction::MoveRight,
+    GameAction::SoftDrop,
+    GameAction::HardDrop,
+    GameAction::RotateCW,
+    GameAction::RotateCCW,
+    GameAction::HoldPiece,
+];
 
+// egui front end for the engine: turns key presses into GameActions, feeds
+// frame time to the engine and draws its state.
 pub struct Game {
-    pub board: Board,
-    pub queue: Queue,
+    pub engine: Engine,
     pub options: GameOptions,
-    pub hold_queue: HoldQueue,
-    pub current_piece: Option<Piece>,
-    level: u32,
-    combo: u32,
-    score_manager: ScoreManager,
-    lines_cleared: u32,
+    mode: GameMode,
     game_state: GameState,
-    drop_timer: f32,
-    drop_interval: f32,
-    current_game_stats: GameStats,
-    game_start_time: u64,
-    lock_delay_timer: f32,
-    lock_delay_duration: f32,
-    piece_on_ground: bool,
-    left_pressed: bool,
-    right_pressed: bool,
-    left_das_timer: f32,
-    right_das_timer: f32,
-
-    //DAS settings (in seconds)
-    das_delay: f32,
-    das_repeat_interval: f32,
+    held_actions: HashSet<GameAction>,
+    // Position Puzzle games start from, kept so restarts replay it
+    puzzle: Option<Puzzle>,
+    // PC practice solver, run again on its worker thread whenever a piece is
+    // placed or held so a long search never holds up the frame
+    pub show_pc_hints: bool,
+    pc_hint: Option<PcHint>,
+    pc_hint_position: Option<(u32, bool)>,
+    pc_solver: Option<PcSolver>,
+    pc_solving: bool,
 }
 
 impl Game {
     pub fn new(options: GameOptions) -> Self {
-        let mut game = Self {
-            board: Board::new(),
-            queue: Queue::new(),
-            options: options,
-            hold_queue: HoldQueue::new(),
-            current_piece: None,
-            level: 1,
-            combo: 0,
-            score_manager: ScoreManager::new(),
-            lines_cleared: 0,
+        Self {
+            engine: Engine::new(options.handling()),
+            options,
+            mode: GameMode::default(),
             game_state: GameState::Menu,
-            drop_timer: 0.0,
-            drop_interval: 1.0,
-            current_game_stats: GameStats::new(),
-            game_start_time: 0,
-            lock_delay_timer: 0.0,
-            lock_delay_duration: 1.0, // 1000ms at level 1
-            piece_on_ground: false,
-            left_pressed: false,
-            right_pressed: false,
-            left_das_timer: 0.0,
-            right_das_timer: 0.0,
-            das_delay: 0.133, // 133ms delay before auto-shift
-            das_repeat_interval: 0.033, // 33ms between auto-shifts
-
-        };
-        
-        // Generate and test 7-bag
-        game.queue.generate_seven_bag();
-        game.current_piece = game.queue.get_next_piece();
-
-    
-        
-        game
+            held_actions: HashSet::new(),
+            puzzle: None,
+            show_pc_hints: false,
+            pc_hint: None,
+            pc_hint_position: None,
+            pc_solver: None,
+            pc_solving: false,
+        }
     }
 
     pub fn update(&mut self, ctx: &egui::Context) {
         self.handle_input(ctx);
-        
-        // Only apply gravity when playing
+
+        // Only advance the engine when playing
         if self.game_state == GameState::Playing {
+            // Pick up option changes made mid-session
+            self.engine.set_handling(self.options.handling());
+
             // Get delta time from egui
             let delta_time = ctx.input(|i| i.stable_dt);
-            
-            // Update drop timer
-            self.drop_timer += delta_time;
-            
-            // Check if it's time to drop the piece
-            if self.drop_timer >= self.drop_interval {
-                self.drop_timer = 0.0;
-                self.apply_gravity();
-            }
-            
-            // Handle lock delay when piece is on ground
-            if self.piece_on_ground {
-                self.lock_delay_timer += delta_time;
-                
-                if self.lock_delay_timer >= self.lock_delay_duration {
-                    self.lock_piece();
-                    self.piece_on_ground = false;
-                    self.lock_delay_timer = 0.0;
-                }
+            self.engine.step(delta_time);
+
+            if self.engine.is_game_over() {
+                self.game_state = GameState::GameOver;
             }
         }
-        
+
+        if self.show_pc_hints && self.engine.get_mode() == GameMode::PerfectClear {
+            self.update_pc_hint();
+        }
+
         // Request repaint for smooth animation
         ctx.request_repaint();
     }
@@ -116,110 +90,108 @@ impl Game {
         self.game_state = state;
     }
 
-    pub fn get_score(&self) -> u32 {
-        self.score_manager.get_score()
+    // Mode used by the next start_game, restarts keep the current one
+    pub fn set_mode(&mut self, mode: GameMode) {
+        self.mode = mode;
     }
 
-    pub fn get_lines_cleared(&self) -> u32 {
-        self.lines_cleared
+    // Position used by the next Puzzle game
+    pub fn set_puzzle(&mut self, puzzle: Puzzle) {
+        self.puzzle = Some(puzzle);
     }
 
-    pub fn get_game_stats(&mut self) -> GameStats {
-        // Update current stats before returning
-        self.current_game_stats.score = self.score_manager.get_score();
-        self.current_game_stats.lines_cleared = self.lines_cleared;
-        self.current_game_stats.level_reached = self.level;
-        
-        // Calculate duration
-        if self.game_start_time > 0 {
-            let now = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
-            self.current_game_stats.duration_seconds = now - self.game_start_time;
-        }
-        
-        self.current_game_stats.clone()
+    // Play time comes from the engine, so pauses and the menu don't count
+    pub fn get_game_stats(&self) -> GameStats {
+        self.engine.get_game_stats()
     }
 
     pub fn draw_game_board(&mut self, ui: &mut egui::Ui) {
-        ui.horizontal(|ui| {
-            // Left side - the game board
-            ui.vertical(|ui | {
-                self.hold_queue.draw(ui);
-            });
-            ui.vertical(|ui| {
-                // Calculate ghost piece position
-                let mut ghost_blocks = Vec::new();
-                if let Some(piece) = &self.current_piece {
-                    let mut ghost_piece = piece.clone();
-                    
-                    // Drop ghost piece until it collides
-                    loop {
-                        ghost_piece.move_down();
-                        if !self.board.is_valid_position(&ghost_piece.get_blocks()) {
-                            ghost_piece.move_up(); // Undo last move
-                            break;
-                        }
-                    }
-                    
-                    // Get ghost blocks with transparent color
-                    let ghost_color = ghost_piece.get_color_with_alpha(60);
-                    for (row, col) in ghost_piece.get_blocks() {
-                        ghost_blocks.push((row, col, ghost_color));
-                    }
+        // Outline where the opener puts the current piece
+        if self.engine.get_mode() == GameMode::Opener {
+            let color = egui::Color32::from_white_alpha(60);
+            let target: Vec<_> = self.engine.get_opener_target()
+                .unwrap_or_default()
+                .iter()
+                .map(|(row, col)| (*row, *col, color))
+                .collect();
+            draw_engine_with_overlay(&mut self.engine, ui, self.options.ghost_piece_alpha, &target);
+            return;
+        }
+
+        if self.engine.get_mode() != GameMode::PerfectClear {
+            draw_engine(&mut self.engine, ui, self.options.ghost_piece_alpha);
+            return;
+        }
+
+        // Outline where the solution puts the current piece
+        let mut hint_blocks = Vec::new();
+        if let (true, Some(PcHint::Possible(steps))) = (self.show_pc_hints, &self.pc_hint) {
+            if let Some(step) = steps.first() {
+                let color = egui::Color32::from_white_alpha(60);
+                hint_blocks.extend(step.blocks.iter().map(|(row, col)| (*row, *col, color)));
+            }
+        }
+        draw_engine_with_overlay(&mut self.engine, ui, self.options.ghost_piece_alpha, &hint_blocks);
+
+        ui.add_space(10.0);
+        ui.checkbox(&mut self.show_pc_hints, "Solver hints");
+        if self.show_pc_hints {
+            match &self.pc_hint {
+                Some(PcHint::Possible(steps)) => {
+                    let path: Vec<String> = steps
+                        .iter()
+                        .map(|step| if step.hold { format!("Hold {}", step.piece) } else { step.piece.clone() })
+                        .collect();
+                    ui.colored_label(egui::Color32::from_rgb(120, 220, 120), "Perfect clear possible");
+                    ui.label(path.join(" → "));
                 }
-              
-                // Temporarily draw current piece on board
-                if let Some(piece) = &self.current_piece {
-                    let blocks = piece.get_blocks();
-                    let id = piece.get_id();
-                    
-                    for (row, col) in &blocks {
-                        if *row >= 0 && *col >= 0 {
-                            self.board.set_cell(*row as usize, *col as usize, id);
-                        }
-                    }
+                Some(PcHint::Impossible) => {
+                    ui.colored_label(egui::Color32::from_rgb(255, 120, 80), "No perfect clear with these pieces");
                 }
-                
-                // Draw board with ghost piece overlay
-                self.board.draw_with_overlay(ui, &ghost_blocks);
-                
-                // Clear piece blocks after drawing
-                if let Some(piece) = &self.current_piece {
-                    let blocks = piece.get_blocks();
-                    for (row, col) in &blocks {
-                        if *row >= 0 && *col >= 0 {
-                            self.board.set_cell(*row as usize, *col as usize, 0);
-                        }
-                    }
+                Some(PcHint::Unknown) => {
+                    ui.label("Too many ways to check, keep going");
                 }
-            });
-            
-            ui.add_space(20.0);
-            
-            // Right side - the queue and stats
-            ui.vertical(|ui| {
-                // Display score and stats
-                ui.heading("Game Stats");
-                ui.add_space(5.0);
-                ui.label(format!("Score: {}", self.score_manager.get_score()));
-                ui.label(format!("Level: {}", self.level));
-                ui.label(format!("Lines: {}", self.lines_cleared));
-                ui.label(format!("Combo: {}", self.combo));
-                
-                ui.add_space(20.0);
-                
-                // Display next pieces
-                self.queue.draw(ui, 5); // Show next 5 pieces
-            });
-        });
+                None if self.pc_solving => {
+                    ui.label("Solving...");
+                }
+                None => {}
+            }
+        }
+    }
+
+    // Pick up a finished search, and start a new one after each placement or hold
+    fn update_pc_hint(&mut self) {
+        let solver = self.pc_solver.get_or_insert_with(PcSolver::new);
+        if let Some(hint) = solver.poll() {
+            self.pc_hint = Some(hint);
+            self.pc_solving = false;
+        }
+
+        let position = (self.engine.get_pieces_placed(), self.engine.hold_queue.can_hold());
+        if self.pc_hint_position == Some(position) {
+            return;
+        }
+        self.pc_hint_position = Some(position);
+        self.pc_hint = None;
+
+        // A new request stops the search for the previous position
+        let engine = &self.engine;
+        match engine.current_piece.as_ref().filter(|_| !engine.is_game_over()) {
+            Some(piece) => {
+                let mut pieces = vec![piece.get_name().to_string()];
+                pieces.extend(engine.queue.get_piece_names());
+                let held = engine.hold_queue.held_piece.as_ref().map(|held| held.get_name().to_string());
+                solver.request(engine.board.clone(), engine.get_pc_height(), pieces, held, engine.hold_queue.can_hold());
+                self.pc_solving = true;
+            }
+            None => {
+                solver.cancel();
+                self.pc_solving = false;
+            }
+        }
     }
 
     fn handle_input(&mut self, ctx: &egui::Context) {
-        // Get delta time for DAS
-        let delta_time = ctx.input(|i| i.stable_dt);
-        
         ctx.input(|i| {
             // Pause/unpause
             if self.is_action_key_pressed(i, GameAction::PauseGame) {
@@ -229,352 +201,90 @@ impl Game {
                     _ => {}
                 }
             }
-            
-            // Only handle game input when playing
-            if self.game_state != GameState::Playing {
-                return;
-            }
-            
-            // Rotate clockwise
-            if self.is_action_key_pressed(i, GameAction::RotateCW) {
-                if let Some(piece) = &mut self.current_piece {
-                    piece.rotate_clockwise();
-                    if !self.board.is_valid_position(&piece.get_blocks()) {
-                        piece.rotate_counterclockwise(); // Undo
-                    } else {
-                        self.reset_lock_delay();
-                    }
-                }
-            }
-
-            // Rotate counterclockwise
-            if self.is_action_key_pressed(i, GameAction::RotateCCW) {
-                if let Some(piece) = &mut self.current_piece {
-                    piece.rotate_counterclockwise();
-                    if !self.board.is_valid_position(&piece.get_blocks()) {
-                        piece.rotate_clockwise(); // Undo
-                    } else {
-                        self.reset_lock_delay();
-                    }
-                }
-            }
-            
-            // Soft drop
-            if self.is_action_key_pressed(i, GameAction::SoftDrop) {
-                if let Some(piece) = &mut self.current_piece {
-                    piece.move_down();
-                    self.score_manager.drop(1, 1);
-                    if !self.board.is_valid_position(&piece.get_blocks()) {
-                        piece.move_up(); // Undo
-                        self.piece_on_ground = true; // Start lock delay
-                    } else {
-                        self.reset_lock_delay();
-                    }
-                }
-            }
-            
-            // Hold piece
-            if self.is_action_key_pressed(i, GameAction::HoldPiece) {
-                self.hold_queue.hold_piece(&mut self.current_piece, &mut self.queue);
-            }
-            
-            // Hard drop
-            if self.is_action_key_pressed(i, GameAction::HardDrop) {
-                self.hard_drop();
-            }
-            
-            // Restart game
-            if i.key_pressed(egui::Key::R) {
-                self.reset_game();
-            }
         });
-        
-        // Handle left/right movement with DAS (Delayed Auto Shift) outside of input closure
-        let left_held = self.is_action_held(ctx, GameAction::MoveLeft);
-        let right_held = self.is_action_held(ctx, GameAction::MoveRight);
-        
-        // Only handle DAS when playing
+
+        // Only handle game input when playing, let go of everything otherwise
         if self.game_state != GameState::Playing {
-            self.left_pressed = false;
-            self.right_pressed = false;
-            self.left_das_timer = 0.0;
-            self.right_das_timer = 0.0;
+            self.held_actions.clear();
+            self.engine.release_all();
             return;
         }
-        
-        // Handle left key with DAS
-        if left_held {
-            if !self.left_pressed {
-                // Key just pressed - immediate movement
-                if let Some(piece) = &mut self.current_piece {
-                    piece.move_left();
-                    if !self.board.is_valid_position(&piece.get_blocks()) {
-                        piece.move_right(); // Undo
-                    } else {
-                        self.reset_lock_delay();
-                    }
-                }
-                self.left_pressed = true;
-                self.left_das_timer = 0.0;
-            } else {
-                // Key held - apply DAS
-                self.left_das_timer += delta_time;
-                
-                if self.left_das_timer >= self.das_delay {
-                    // DAS delay passed, now auto-repeat at ARR rate
-                    let time_since_das = self.left_das_timer - self.das_delay;
-                    let moves = (time_since_das / self.das_repeat_interval) as i32;
-                    
-                    if moves > 0 {
-                        if let Some(piece) = &mut self.current_piece {
-                            piece.move_left();
-                            if !self.board.is_valid_position(&piece.get_blocks()) {
-                                piece.move_right(); // Undo
-                            } else {
-                                self.reset_lock_delay();
-                            }
-                        }
-                        self.left_das_timer = self.das_delay + (time_since_das % self.das_repeat_interval);
-                    }
-                }
-            }
-        } else {
-            self.left_pressed = false;
-            self.left_das_timer = 0.0;
-        }
 
-        // Handle right key with DAS
-        if right_held {
-            if !self.right_pressed {
-                // Key just pressed - immediate movement
-                if let Some(piece) = &mut self.current_piece {
-                    piece.move_right();
-                    if !self.board.is_valid_position(&piece.get_blocks()) {
-                        piece.move_left(); // Undo
-                    } else {
-                        self.reset_lock_delay();
-                    }
-                }
-                self.right_pressed = true;
-                self.right_das_timer = 0.0;
-            } else {
-                // Key held - apply DAS
-                self.right_das_timer += delta_time;
-                
-                if self.right_das_timer >= self.das_delay {
-                    // DAS delay passed, now auto-repeat at ARR rate
-                    let time_since_das = self.right_das_timer - self.das_delay;
-                    let moves = (time_since_das / self.das_repeat_interval) as i32;
-                    
-                    if moves > 0 {
-                        if let Some(piece) = &mut self.current_piece {
-                            piece.move_right();
-                            if !self.board.is_valid_position(&piece.get_blocks()) {
-                                piece.move_left(); // Undo
-                            } else {
-                                self.reset_lock_delay();
-                            }
-                        }
-                        self.right_das_timer = self.das_delay + (time_since_das % self.das_repeat_interval);
-                    }
-                }
-            }
-        } else {
-            self.right_pressed = false;
-            self.right_das_timer = 0.0;
+        // Restart game
+        if ctx.input(|i| self.is_action_key_pressed(i, GameAction::RestartGame)) {
+            self.reset_game();
+            return;
         }
-    }
-    fn is_action_pressed(&self, ctx: &egui::Context, action: GameAction) -> bool {
-        let key = self.options.key_bindings.get(&action);
-        ctx.input(|i| key.map_or(false, |k| i.key_down(*k)))
-    }
-    fn hard_drop(&mut self) {
-        if let Some(piece) = &mut self.current_piece {
-            let mut cells_dropped = 0;
-            
-            // Move piece down until it collides
-            loop {
-                piece.move_down();
-                if !self.board.is_valid_position(&piece.get_blocks()) {
-                    piece.move_up(); // Undo last move
-                    break;
-                }
-                cells_dropped += 1;
+
+        // Turn key state changes into engine presses and releases. Key-down edges are
+        // used instead of key_pressed so OS key repeat doesn't retrigger actions.
+        for action in ENGINE_ACTIONS {
+            let (down, tapped) = match self.options.key_bindings.get(&action) {
+                Some(key) => ctx.input(|i| (i.key_down(*key), i.key_pressed(*key))),
+                None => (false, false),
+            };
+            let was_down = self.held_actions.contains(&action);
+
+            if (down || tapped) && !was_down {
+                self.engine.press(action);
+                self.held_actions.insert(action);
             }
-            
-            // Award hard drop points (2 points per cell)
-            if cells_dropped > 0 {
-                self.score_manager.drop(2, cells_dropped);
+            if !down && (was_down || tapped) {
+                self.engine.release(action);
+                self.held_actions.remove(&action);
             }
         }
-        
-        // Lock the piece immediately (bypass lock delay)
-        self.piece_on_ground = false;
-        self.lock_delay_timer = 0.0;
-        self.lock_piece();
-    }
-    
-    fn reset_lock_delay(&mut self) {
-        // Reset lock delay timer when piece is moved/rotated successfully
-        if self.piece_on_ground {
-            self.lock_delay_timer = 0.0;
-        }
     }
 
-    fn lock_piece(&mut self) {
-        if let Some(piece) = &self.current_piece {
-            let blocks = piece.get_blocks();
-            let id = piece.get_id();
-            
-            // Place piece blocks permanently on the board
-            for (row, col) in &blocks {
-                if *row >= 0 && *col >= 0 {
-                    self.board.set_cell(*row as usize, *col as usize, id);
-                }
-            }
-            
-            // Track pieces placed
-            self.current_game_stats.pieces_placed += 1;
-        }
-        
-        // Clear completed lines
-        let cleared = self.board.clear_lines();
-        if cleared > 0 {
-            // Track line clears by type
-            match cleared {
-                1 => self.current_game_stats.singles += 1,
-                2 => self.current_game_stats.doubles += 1,
-                3 => self.current_game_stats.triples += 1,
-                4 => self.current_game_stats.quadruples += 1,
-                _ => {}
-            }
-            
-            // Increment combo
-            self.combo += 1;
-            
-            // Track max combo
-            if self.combo > self.current_game_stats.max_combo {
-                self.current_game_stats.max_combo = self.combo;
-            }
-            
-            // Use score manager to handle scoring
-            self.score_manager.lines_cleared(cleared, self.level, self.combo);
-            self.lines_cleared += cleared;
-            
-            // Check for level up
-            self.update_level();
-            
-            println!("Cleared {} line(s)! Combo: {} Total lines: {}", cleared, self.combo, self.lines_cleared);
-            println!("Score: {}", self.score_manager.get_score());
-        } else {
-            // Reset combo if no lines cleared
-            self.combo = 0;
-        }
-        
-        // Reset hold permission after locking
-        self.hold_queue.reset_hold();
-        
-        // Spawn next piece
-        self.spawn_next_piece();
+    pub fn start_game(&mut self) {
+        self.start_game_with_seed(rand::random());
     }
 
-    fn spawn_next_piece(&mut self) {
-        self.current_piece = self.queue.get_next_piece();
-        
-        if let Some(piece) = &self.current_piece {
-            // Check if the new piece collides immediately (game over condition)
-            if !self.board.is_valid_position(&piece.get_blocks()) {
-                println!("Game Over! No space for new piece.");
-                self.current_piece = None; // Clear the piece that couldn't spawn
-                self.game_state = GameState::GameOver;
-            }
+    // Versus players share a seed so both get the same pieces
+    pub fn start_game_with_seed(&mut self, seed: u64) {
+        let puzzle = self.puzzle.as_ref().filter(|_| self.mode == GameMode::Puzzle);
+        let opener = (self.mode == GameMode::Opener).then_some(self.options.opener);
+        // Openers are built around the 7-bag, whatever randomizer is picked
+        let randomizer = if opener.is_some() { RandomizerKind::SevenBag } else { self.options.randomizer };
+        let mut queue = Queue::with_randomizer(randomizer, seed);
+        if let Some(puzzle) = puzzle {
+            queue = queue.with_preset(puzzle.queue.clone());
         }
-    }
-    
-    fn apply_gravity(&mut self) {
-        if let Some(piece) = &mut self.current_piece {
-            piece.move_down();
-            
-            // Check if the move is valid
-            if !self.board.is_valid_position(&piece.get_blocks()) {
-                piece.move_up(); // Undo the move
-                self.piece_on_ground = true; // Piece is now resting on something
-            } else {
-                // Piece successfully moved down, reset lock delay
-                self.piece_on_ground = false;
-                self.lock_delay_timer = 0.0;
-            }
+        if let Some(opener) = opener {
+            queue = queue.with_bags(&opener.pieces());
         }
-    }
-    
-    fn calculate_drop_interval(&self) -> f32 {
-        // Decrease interval as level increases (faster falling)
-        // Formula: (0.8 - ((level - 1) * 0.007))^(level - 1)
-        // This creates a good curve similar to official Tetris
-        let base_interval = 1.0;
-        let level_multiplier = 0.9_f32.powi(self.level as i32 - 1);
-        (base_interval * level_multiplier).max(0.1) // Minimum 0.1 seconds
-    }
-    
-    fn calculate_lock_delay(&self) -> f32 {
-        // Decrease lock delay as level increases
-        // Start at 500ms, reduce to minimum 100ms at high levels
-        let base_delay = 0.5;
-        let level_multiplier = 0.92_f32.powi(self.level as i32 - 1);
-        (base_delay * level_multiplier).max(0.1) // Minimum 100ms
-    }
-    
-    fn update_level(&mut self) {
-        // Increase level every 10 lines
-        let new_level = (self.lines_cleared / 10) + 1;
-        if new_level != self.level {
-            self.level = new_level;
-            self.drop_interval = self.calculate_drop_interval();
-            self.lock_delay_duration = self.calculate_lock_delay();
-            println!("Level up! Now at level {}", self.level);
+        let line_cap = self.options.marathon_line_cap.filter(|_| self.mode == GameMode::Marathon);
+        let starting_board = match self.mode {
+            GameMode::PerfectClear => Some(self.options.pc_setup.board_ids()),
+            _ => puzzle.map(|puzzle| puzzle.cells.clone()),
+        };
+        self.engine = Engine::with_mode(self.options.handling(), queue, self.mode)
+            .with_line_cap(line_cap)
+            .with_starting_board(starting_board)
+            .with_starting_hold(puzzle.and_then(|puzzle| puzzle.hold.clone()))
+            .with_opener(opener);
+        self.held_actions.clear();
+        self.pc_hint = None;
+        self.pc_hint_position = None;
+        if let Some(solver) = &self.pc_solver {
+            solver.cancel();
         }
-    }
-    
-    pub fn start_game(&mut self) {
+        self.pc_solving = false;
         self.game_state = GameState::Playing;
-        self.queue.generate_seven_bag();
-        self.current_piece = self.queue.get_next_piece();
-        self.drop_interval = self.calculate_drop_interval();
-        self.lock_delay_duration = self.calculate_lock_delay();
-        
-        // Reset and start tracking game stats
-        self.current_game_stats = GameStats::new();
-        self.game_start_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
     }
 
     pub fn resume_game(&mut self) {
         self.game_state = GameState::Playing;
     }
-    
+
     pub fn is_game_active(&self) -> bool {
         self.game_state == GameState::Playing || self.game_state == GameState::Paused
     }
-    
+
     pub fn reset_game(&mut self) {
-        let options = self.options.clone();
-        *self = Self::new(options);
-        self.game_state = GameState::Playing;
-        self.queue.generate_seven_bag();
-        self.current_piece = self.queue.get_next_piece();
-        self.drop_interval = self.calculate_drop_interval();
-        self.lock_delay_duration = self.calculate_lock_delay();
-        
-        // Reset and start tracking game stats
-        self.current_game_stats = GameStats::new();
-        self.game_start_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        self.start_game();
     }
-    
+
     // Helper method for checking if action key was pressed (single press)
     fn is_action_key_pressed(&self, input: &egui::InputState, action: GameAction) -> bool {
         if let Some(key) = self.options.key_bindings.get(&action) {
@@ -583,13 +293,203 @@ impl Game {
             false
         }
     }
+}
 
-    // Helper method for checking if action key is held down
-    fn is_action_held(&self, ctx: &egui::Context, action: GameAction) -> bool {
-        if let Some(key) = self.options.key_bindings.get(&action) {
-            ctx.input(|i| i.key_down(*key))
-        } else {
-            false
+// Draws hold, board with ghost piece, stats and next queue for any engine,
+// shared by the live game and the replay viewer
+pub fn draw_engine(engine: &mut Engine, ui: &mut egui::Ui, ghost_alpha: u8) {
+    draw_engine_with_overlay(engine, ui, ghost_alpha, &[]);
+}
+
+// Same as draw_engine with extra blocks drawn over the board, like hints
+pub fn draw_engine_with_overlay(engine: &mut Engine, ui: &mut egui::Ui, ghost_alpha: u8, overlay: &[(i32, i32, egui::Color32)]) {
+    ui.horizontal(|ui| {
+        // Left side - the game board
+        ui.vertical(|ui | {
+            engine.hold_queue.draw(ui);
+        });
+
+        // Incoming versus garbage meter next to the board
+        if engine.get_mode() == GameMode::Versus {
+            draw_garbage_meter(ui, engine);
         }
+        ui.vertical(|ui| {
+            // Get ghost blocks with transparent color
+            let mut ghost_blocks = Vec::new();
+            if let Some(ghost_piece) = engine.get_ghost_piece() {
+                let ghost_color = ghost_piece.get_color_with_alpha(ghost_alpha);
+                for (row, col) in ghost_piece.get_blocks() {
+                    ghost_blocks.push((row, col, ghost_color));
+                }
+            }
+            ghost_blocks.extend_from_slice(overlay);
+
+            // Temporarily draw current piece on board
+            if let Some(piece) = &engine.current_piece {
+                let blocks = piece.get_blocks();
+                let id = piece.get_id();
+
+                for (row, col) in &blocks {
+                    if *row >= 0 && *col >= 0 {
+                        engine.board.set_cell(*row as usize, *col as usize, id);
+                    }
+                }
+            }
+
+            // Draw board with ghost piece overlay
+            engine.board.draw_with_overlay(ui, &ghost_blocks);
+
+            // Clear piece blocks after drawing
+            if let Some(piece) = &engine.current_piece {
+                let blocks = piece.get_blocks();
+                for (row, col) in &blocks {
+                    if *row >= 0 && *col >= 0 {
+                        engine.board.set_cell(*row as usize, *col as usize, 0);
+                    }
+                }
+            }
+        });
+
+        ui.add_space(20.0);
+
+        // Right side - the queue and stats
+        ui.vertical(|ui| {
+            // Display score and stats
+            ui.heading("Game Stats");
+            ui.add_space(5.0);
+            match engine.get_mode() {
+                GameMode::Marathon => {
+                    ui.label(format!("Score: {}", engine.get_score()));
+                    ui.label(format!("Level: {}", engine.get_level()));
+                    match engine.get_line_cap() {
+                        Some(cap) => ui.label(format!("Lines: {}/{}", engine.get_lines_cleared().min(cap), cap)),
+                        None => ui.label(format!("Lines: {}", engine.get_lines_cleared())),
+                    };
+                }
+                GameMode::Ultra => {
+                    let remaining = engine.get_time_remaining_ms().unwrap_or(0);
+                    ui.label(format!("Time Left: {}", format_time(remaining)));
+                    ui.label(format!("Score: {}", engine.get_score()));
+                    ui.label(format!("Lines: {}", engine.get_lines_cleared()));
+                }
+                GameMode::Master => {
+                    let level = engine.get_level();
+                    ui.label(format!("Level: {}/{}", level, master::section_stop(level) + 1));
+                    ui.label(format!("Time: {}", format_time(engine.get_time_ms())));
+                    ui.label(format!("Lines: {}", engine.get_lines_cleared()));
+                    ui.label(format!("Score: {}", engine.get_score()));
+                }
+                GameMode::Dig => {
+                    ui.label(format!("Time: {}", format_time(engine.get_time_ms())));
+                    ui.label(format!("Garbage: {}/{}", engine.get_garbage_remaining(), DIG_GARBAGE_ROWS));
+                    ui.label(format!("Pieces: {}", engine.get_pieces_placed()));
+                }
+                GameMode::Versus => {
+                    ui.label(format!("Lines: {}", engine.get_lines_cleared()));
+                    ui.label(format!("Incoming: {}", engine.get_incoming_garbage()));
+                }
+                GameMode::Survival => {
+                    ui.label(format!("Time: {}", format_time(engine.get_time_ms())));
+                    let countdown = engine.get_garbage_countdown_ms().unwrap_or(0);
+                    ui.label(format!("Next Garbage: {:.1}s", countdown as f32 / 1000.0));
+                    ui.label(format!("Lines: {}", engine.get_lines_cleared()));
+                }
+                GameMode::PerfectClear => {
+                    ui.label(format!("Time: {}", format_time(engine.get_time_ms())));
+                    ui.label(format!("Lines: {}/{}", (engine.get_lines_cleared() as usize).min(PC_HEIGHT), PC_HEIGHT));
+                    ui.label(format!("Pieces: {}", engine.get_pieces_placed()));
+                }
+                GameMode::Opener => {
+                    let grades = engine.get_opener_grades();
+                    if let Some(opener) = engine.get_opener() {
+                        ui.label(format!("Opener: {}", opener.display_name()));
+                        ui.label(format!("Placements: {}/{}", grades.len(), opener.steps().len()));
+                    }
+                    ui.label(format!("Time: {}", format_time(engine.get_time_ms())));
+                    let perfect = grades.iter().filter(|grade| **grade == PlacementGrade::Perfect).count();
+                    let good = grades.iter().filter(|grade| **grade == PlacementGrade::Good).count();
+                    ui.label(format!("Perfect: {}  Good: {}", perfect, good));
+                    if let Some(grade) = grades.last() {
+                        ui.colored_label(grade_color(*grade), format!("Last piece: {}", grade.display_name()));
+                    }
+                    // Openers that save a piece for later need it held first
+                    if let (Some(step), Some(piece)) = (engine.get_opener_step(), &engine.current_piece) {
+                        if piece.get_name() != step.piece {
+                            ui.label(format!("Hold the {} for later", piece.get_name()));
+                        }
+                    }
+                }
+                GameMode::Puzzle => {
+                    ui.label(format!("Score: {}", engine.get_score()));
+                    ui.label(format!("Lines: {}", engine.get_lines_cleared()));
+                    ui.label(format!("Pieces: {}", engine.get_pieces_placed()));
+                }
+                GameMode::Sprint => {
+                    ui.label(format!("Time: {}", format_time(engine.get_time_ms())));
+                    ui.label(format!("Lines: {}/{}", engine.get_lines_cleared().min(SPRINT_LINES), SPRINT_LINES));
+                    ui.label(format!("Score: {}", engine.get_score()));
+                }
+            }
+            ui.label(format!("Combo: {}", engine.get_combo()));
+            if engine.get_back_to_back_chain() > 0 {
+                ui.label(format!("B2B x{}", engine.get_back_to_back_chain()));
+            }
+            if engine.get_t_spins() > 0 {
+                ui.label(format!("T-Spins: {}", engine.get_t_spins()));
+            }
+            if engine.get_perfect_clears() > 0 {
+                ui.label(format!("Perfect Clears: {}", engine.get_perfect_clears()));
+            }
+            ui.label(format!("PPS: {:.2}  KPP: {:.2}", engine.get_pps(), engine.get_kpp()));
+            ui.label(format!("LPM: {:.1}  APM: {:.1}", engine.get_lines_per_minute(), engine.get_attack_per_minute()));
+
+            // Live finesse, the last piece is called out when it took extra inputs
+            if engine.get_mode() != GameMode::Versus {
+                ui.label(format!("Finesse Faults: {}", engine.get_finesse_faults()));
+                if let Some(result) = engine.get_last_finesse().filter(|result| result.is_fault()) {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(255, 120, 80),
+                        format!("Last piece: {} inputs ({} needed)", result.inputs, result.minimum),
+                    );
+                }
+            }
+
+            ui.add_space(20.0);
+
+            // Display next pieces, PC practice shows enough for a whole clear
+            let previews = if engine.get_mode() == GameMode::PerfectClear { PC_PREVIEW } else { 5 };
+            engine.queue.draw(ui, previews);
+        });
+    });
+}
+
+// Red bar showing how many garbage rows are waiting to rise, to the scale of the board
+fn draw_garbage_meter(ui: &mut egui::Ui, engine: &Engine) {
+    let cell_size = engine.board.cell_size;
+    let height = Board::get_height() as f32 * cell_size;
+    let (rect, _response) = ui.allocate_exact_size(egui::vec2(8.0, height), egui::Sense::hover());
+    let painter = ui.painter();
+
+    painter.rect_filled(rect, 0.0, egui::Color32::from_rgb(40, 40, 50));
+    let rows = (engine.get_incoming_garbage() as f32).min(Board::get_height() as f32);
+    if rows > 0.0 {
+        let filled = egui::Rect::from_min_max(
+            egui::pos2(rect.min.x, rect.max.y - rows * cell_size),
+            rect.max,
+        );
+        painter.rect_filled(filled, 0.0, egui::Color32::from_rgb(220, 40, 40));
+    }
+}
+
+pub fn grade_color(grade: PlacementGrade) -> egui::Color32 {
+    match grade {
+        PlacementGrade::Perfect => egui::Color32::from_rgb(120, 220, 120),
+        PlacementGrade::Good => egui::Color32::from_rgb(230, 210, 90),
+        PlacementGrade::Miss => egui::Color32::from_rgb(255, 120, 80),
     }
-}
\ No newline at end of file
+}
+
+// Formats a duration as m:ss.mmm
+pub fn format_time(ms: u64) -> String {
+    format!("{}:{:02}.{:03}", ms / 60_000, ms / 1000 % 60, ms % 1000)
+}
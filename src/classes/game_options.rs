@@ -1,30 +1,61 @@
+use crate::classes::engine::Handling;
+use crate::classes::opener::Opener;
+use crate::classes::perfect_clear::PcSetup;
+use crate::classes::randomizer::RandomizerKind;
 use crate::enums::game_actions::GameAction;
 use egui::ahash::{HashMap, HashMapExt};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 // Helper functions to convert egui::Key to/from string
+// Saved under egui's own key name, the one `Key::from_name` reads back
 fn key_to_string(key: &egui::Key) -> String {
-    format!("{:?}", key)
+    key.name().to_string()
 }
 
 fn string_to_key(s: &str) -> Option<egui::Key> {
-    match s {
-        "ArrowLeft" => Some(egui::Key::ArrowLeft),
-        "ArrowRight" => Some(egui::Key::ArrowRight),
-        "ArrowDown" => Some(egui::Key::ArrowDown),
-        "ArrowUp" => Some(egui::Key::ArrowUp),
-        "Space" => Some(egui::Key::Space),
-        "Z" => Some(egui::Key::Z),
-        "X" => Some(egui::Key::X),
-        "C" => Some(egui::Key::C),
-        "R" => Some(egui::Key::R),
-        "P" => Some(egui::Key::P),
-        "Escape" => Some(egui::Key::Escape),
-        _ => None,
+    egui::Key::from_name(s)
+}
+
+fn bindings_to_strings(bindings: &HashMap<GameAction, egui::Key>) -> Vec<(GameAction, String)> {
+    bindings
+        .iter()
+        .map(|(action, key)| (*action, key_to_string(key)))
+        .collect()
+}
+
+fn bindings_from_strings(bindings: Vec<(GameAction, String)>) -> HashMap<GameAction, egui::Key> {
+    let mut key_bindings = HashMap::default();
+    for (action, key_str) in bindings {
+        if let Some(key) = string_to_key(&key_str) {
+            key_bindings.insert(action, key);
+        }
     }
+    key_bindings
+}
+
+// Second player's keys for local versus, on the left of the keyboard
+fn default_player2_bindings() -> HashMap<GameAction, egui::Key> {
+    let mut key_bindings = HashMap::new();
+    key_bindings.insert(GameAction::MoveLeft, egui::Key::A);
+    key_bindings.insert(GameAction::MoveRight, egui::Key::D);
+    key_bindings.insert(GameAction::SoftDrop, egui::Key::S);
+    key_bindings.insert(GameAction::HardDrop, egui::Key::W);
+    key_bindings.insert(GameAction::RotateCW, egui::Key::E);
+    key_bindings.insert(GameAction::RotateCCW, egui::Key::Q);
+    key_bindings.insert(GameAction::HoldPiece, egui::Key::F);
+    key_bindings
+}
+
+fn default_soft_drop_factor() -> f32 {
+    20.0
+}
+
+fn default_randomizer() -> RandomizerKind {
+    RandomizerKind::SevenBag
 }
 
 // Serializable version of key bindings
+#[allow(dead_code)]
 #[derive(Serialize, Deserialize)]
 struct SerializableBindings {
     bindings: Vec<(GameAction, String)>,
@@ -33,9 +64,19 @@ struct SerializableBindings {
 #[derive(Debug, Clone)]
 pub struct GameOptions {
     pub key_bindings: HashMap<GameAction, egui::Key>,
+    pub player2_key_bindings: HashMap<GameAction, egui::Key>,
     pub ghost_piece_alpha: u8,
     pub das_delay: f32,
     pub arr_delay: f32,
+    pub soft_drop_factor: f32,
+    pub instant_soft_drop: bool,
+    pub randomizer: RandomizerKind,
+    // None plays Marathon endlessly
+    pub marathon_line_cap: Option<u32>,
+    // Board PC practice starts from
+    pub pc_setup: PcSetup,
+    // Opener drilled in the Opener Trainer
+    pub opener: Opener,
 }
 
 impl Default for GameOptions {
@@ -53,9 +94,16 @@ impl Default for GameOptions {
         key_bindings.insert(GameAction::ResumeGame, egui::Key::Escape);
         GameOptions {
             key_bindings,
+            player2_key_bindings: default_player2_bindings(),
             ghost_piece_alpha: 100,
             das_delay: 150.0,
             arr_delay: 50.0,
+            soft_drop_factor: default_soft_drop_factor(),
+            instant_soft_drop: false,
+            randomizer: RandomizerKind::SevenBag,
+            marathon_line_cap: None,
+            pc_setup: PcSetup::default(),
+            opener: Opener::default(),
         }
     }
 }
@@ -67,17 +115,18 @@ impl Serialize for GameOptions {
     {
         use serde::ser::SerializeStruct;
 
-        let bindings: Vec<(GameAction, String)> = self
-            .key_bindings
-            .iter()
-            .map(|(action, key)| (*action, key_to_string(key)))
-            .collect();
-
-        let mut state = serializer.serialize_struct("GameOptions", 4)?;
-        state.serialize_field("key_bindings", &bindings)?;
+        let mut state = serializer.serialize_struct("GameOptions", 11)?;
+        state.serialize_field("key_bindings", &bindings_to_strings(&self.key_bindings))?;
+        state.serialize_field("player2_key_bindings", &bindings_to_strings(&self.player2_key_bindings))?;
         state.serialize_field("ghost_piece_alpha", &self.ghost_piece_alpha)?;
         state.serialize_field("das_delay", &self.das_delay)?;
         state.serialize_field("arr_delay", &self.arr_delay)?;
+        state.serialize_field("soft_drop_factor", &self.soft_drop_factor)?;
+        state.serialize_field("instant_soft_drop", &self.instant_soft_drop)?;
+        state.serialize_field("randomizer", &self.randomizer)?;
+        state.serialize_field("marathon_line_cap", &self.marathon_line_cap)?;
+        state.serialize_field("pc_setup", &self.pc_setup)?;
+        state.serialize_field("opener", &self.opener)?;
         state.end()
     }
 }
@@ -90,25 +139,43 @@ impl<'de> Deserialize<'de> for GameOptions {
         #[derive(Deserialize)]
         struct Helper {
             key_bindings: Vec<(GameAction, String)>,
+            #[serde(default)]
+            player2_key_bindings: Option<Vec<(GameAction, String)>>,
             ghost_piece_alpha: u8,
             das_delay: f32,
             arr_delay: f32,
+            // Settings added later default when missing from older files
+            #[serde(default = "default_soft_drop_factor")]
+            soft_drop_factor: f32,
+            #[serde(default)]
+            instant_soft_drop: bool,
+            #[serde(default = "default_randomizer")]
+            randomizer: RandomizerKind,
+            #[serde(default)]
+            marathon_line_cap: Option<u32>,
+            #[serde(default)]
+            pc_setup: PcSetup,
+            #[serde(default)]
+            opener: Opener,
         }
 
         let helper = Helper::deserialize(deserializer)?;
 
-        let mut key_bindings = HashMap::default();
-        for (action, key_str) in helper.key_bindings {
-            if let Some(key) = string_to_key(&key_str) {
-                key_bindings.insert(action, key);
-            }
-        }
-
         Ok(GameOptions {
-            key_bindings,
+            key_bindings: bindings_from_strings(helper.key_bindings),
+            player2_key_bindings: helper
+                .player2_key_bindings
+                .map(bindings_from_strings)
+                .unwrap_or_else(default_player2_bindings),
             ghost_piece_alpha: helper.ghost_piece_alpha,
             das_delay: helper.das_delay,
             arr_delay: helper.arr_delay,
+            soft_drop_factor: helper.soft_drop_factor,
+            instant_soft_drop: helper.instant_soft_drop,
+            randomizer: helper.randomizer,
+            marathon_line_cap: helper.marathon_line_cap,
+            pc_setup: helper.pc_setup,
+            opener: helper.opener,
         })
     }
 }
@@ -120,10 +187,39 @@ impl GameOptions {
         Ok(())
     }
 
+    // Handling settings the engine needs, without the egui key bindings
+    pub fn handling(&self) -> Handling {
+        Handling {
+            das_delay: self.das_delay,
+            arr_delay: self.arr_delay,
+            soft_drop_factor: self.soft_drop_factor,
+            instant_soft_drop: self.instant_soft_drop,
+        }
+    }
+
+    // Options for the second versus player, same handling with their own keys
+    pub fn for_player2(&self) -> Self {
+        let mut options = self.clone();
+        options.key_bindings = self.player2_key_bindings.clone();
+        options
+    }
+
     pub fn load() -> Self {
         std::fs::read_to_string("settings.json")
             .ok()
             .and_then(|s| serde_json::from_str(&s).ok())
             .unwrap_or_default()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_key_reads_back_as_itself() {
+        for key in egui::Key::ALL {
+            assert_eq!(string_to_key(&key_to_string(key)), Some(*key), "{:?}", key);
+        }
+    }
+}
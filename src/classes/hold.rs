@@ -33,6 +33,11 @@ impl HoldQueue {
         self.can_hold = false; // Disable further holds until piece is locked
     }
 
+    // Whether holding is allowed before the current piece locks
+    pub fn can_hold(&self) -> bool {
+        self.can_hold
+    }
+
     pub fn reset_hold(&mut self) {
         self.can_hold = true;
     }
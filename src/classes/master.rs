@@ -0,0 +1,78 @@
+// Master mode timing. Gravity is always 20G, the challenge is the delays around
+// each piece tightening every 100 levels like the TGM games.
+
+// Reaching this level finishes the game
+pub const MASTER_MAX_LEVEL: u32 = 999;
+
+// Levels per section, each section has its own timing
+pub const SECTION_LEVELS: u32 = 100;
+
+// Delays for one section, in frames
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MasterTiming {
+    // Spawn delay after a piece locks
+    pub are: u32,
+    // Extra delay after a piece clears lines
+    pub line_clear: u32,
+    pub das: u32,
+    pub lock: u32,
+}
+
+const SECTION_TIMINGS: [MasterTiming; 10] = [
+    MasterTiming { are: 27, line_clear: 40, das: 16, lock: 30 },
+    MasterTiming { are: 27, line_clear: 40, das: 16, lock: 30 },
+    MasterTiming { are: 27, line_clear: 35, das: 14, lock: 30 },
+    MasterTiming { are: 25, line_clear: 30, das: 12, lock: 30 },
+    MasterTiming { are: 25, line_clear: 25, das: 10, lock: 30 },
+    MasterTiming { are: 25, line_clear: 16, das: 8, lock: 30 },
+    MasterTiming { are: 16, line_clear: 12, das: 8, lock: 26 },
+    MasterTiming { are: 12, line_clear: 6, das: 8, lock: 22 },
+    MasterTiming { are: 6, line_clear: 6, das: 8, lock: 18 },
+    MasterTiming { are: 6, line_clear: 6, das: 6, lock: 15 },
+];
+
+pub fn section(level: u32) -> usize {
+    ((level / SECTION_LEVELS) as usize).min(SECTION_TIMINGS.len() - 1)
+}
+
+pub fn timing(level: u32) -> MasterTiming {
+    SECTION_TIMINGS[section(level)]
+}
+
+// Level the counter stops at until a line is cleared, the end of the current section
+pub fn section_stop(level: u32) -> u32 {
+    ((level / SECTION_LEVELS + 1) * SECTION_LEVELS - 1).min(MASTER_MAX_LEVEL - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_section_stops_at_its_x99_level() {
+        assert_eq!(section_stop(0), 99);
+        assert_eq!(section_stop(98), 99);
+        // Already at the stop, a piece spawn doesn't move it on
+        assert_eq!(section_stop(99), 99);
+        // A line clear that crosses 99 moves the stop to the next section
+        assert_eq!(section_stop(100), 199);
+        assert_eq!(section_stop(899), 899);
+    }
+
+    #[test]
+    fn the_last_section_stops_a_level_short_of_the_end() {
+        assert_eq!(section_stop(900), MASTER_MAX_LEVEL - 1);
+        assert_eq!(section_stop(998), MASTER_MAX_LEVEL - 1);
+    }
+
+    #[test]
+    fn timing_changes_on_the_first_level_of_a_section() {
+        for (level, section) in [(0, 0), (99, 0), (100, 1), (199, 1), (200, 2), (899, 8), (900, 9), (MASTER_MAX_LEVEL, 9)] {
+            assert_eq!(timing(level), SECTION_TIMINGS[section], "level {}", level);
+        }
+        assert_eq!(timing(199).line_clear, 40);
+        assert_eq!(timing(200).line_clear, 35);
+        assert_eq!(timing(899).lock, 18);
+        assert_eq!(timing(900).lock, 15);
+    }
+}
@@ -1,11 +1,24 @@
+pub mod attack;
 pub mod board;
+pub mod bot;
+pub mod engine;
+pub mod finesse;
 pub mod game;
+pub mod master;
+pub mod opener;
+pub mod perfect_clear;
 pub mod piece;
+pub mod puzzle;
 pub mod queue;
+pub mod randomizer;
+pub mod replay;
+pub mod versus;
 pub mod score_manager;
 pub mod hold;
 pub mod screens;
 pub mod screen_manager;
 pub mod player;
 pub mod database;
-pub mod game_options;
\ No newline at end of file
+pub mod game_options;
+pub mod network;
+pub mod net_versus;
\ No newline at end of file
@@ -0,0 +1,326 @@
+use super::board::Board;
+use super::game::Game;
+use super::game_options::GameOptions;
+use super::network::{BoardSnapshot, Connection, ConnectionEvent, NetMessage, PROTOCOL_VERSION};
+use super::randomizer::RandomizerKind;
+use crate::enums::{game_actions::GameAction, game_modes::GameMode, states::GameState};
+
+// Boards are drawn smaller so both fit side by side
+const NET_VERSUS_CELL_SIZE: f32 = 20.0;
+
+// Frames between board snapshots, 20 a second is plenty to draw the opponent
+const SNAPSHOT_INTERVAL_FRAMES: u64 = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetStatus {
+    Handshaking,
+    WaitingForStart,
+    Playing,
+    Won,
+    Lost,
+    // Both sides topped out in the same frame and their GameOvers crossed
+    Draw,
+    Disconnected(String),
+}
+
+// Versus against another rusttris over TCP. Each side simulates its own game
+// and sends the other snapshots to draw, plus the attacks it sends.
+pub struct NetVersusMatch {
+    pub game: Game,
+    connection: Connection,
+    is_host: bool,
+    pub opponent_name: Option<String>,
+    pub opponent: Option<BoardSnapshot>,
+    status: NetStatus,
+    last_snapshot_frame: Option<u64>,
+}
+
+impl NetVersusMatch {
+    pub fn new(options: &GameOptions, player_name: &str, connection: Connection, is_host: bool) -> Self {
+        // Neither side can stop the clock or restart on their own
+        let mut options = options.clone();
+        options.key_bindings.remove(&GameAction::PauseGame);
+        options.key_bindings.remove(&GameAction::RestartGame);
+
+        let mut net_match = Self {
+            game: Game::new(options),
+            connection,
+            is_host,
+            opponent_name: None,
+            opponent: None,
+            status: NetStatus::Handshaking,
+            last_snapshot_frame: None,
+        };
+        net_match.send(NetMessage::Hello {
+            version: PROTOCOL_VERSION,
+            name: player_name.to_string(),
+        });
+        net_match
+    }
+
+    pub fn update(&mut self, ctx: &egui::Context) {
+        self.receive();
+
+        if self.status != NetStatus::Playing {
+            return;
+        }
+
+        self.game.update(ctx);
+
+        let attack = self.game.engine.take_attack();
+        if attack > 0 {
+            self.send(NetMessage::Attack { lines: attack });
+        }
+
+        let frame = self.game.engine.get_frame();
+        let game_over = self.game.engine.is_game_over();
+        let snapshot_due = self.last_snapshot_frame
+            .is_none_or(|last| frame >= last + SNAPSHOT_INTERVAL_FRAMES);
+        // The losing board always goes out so the opponent sees the top out
+        if snapshot_due || game_over {
+            self.last_snapshot_frame = Some(frame);
+            let snapshot = self.snapshot();
+            self.send(NetMessage::Snapshot(snapshot));
+        }
+
+        if game_over {
+            self.top_out();
+        }
+    }
+
+    fn top_out(&mut self) {
+        if self.status == NetStatus::Playing {
+            self.send(NetMessage::GameOver);
+            self.status = NetStatus::Lost;
+        }
+    }
+
+    // Handle everything the opponent sent since the last frame
+    fn receive(&mut self) {
+        while let Some(event) = self.connection.poll() {
+            match event {
+                ConnectionEvent::Message(message) => self.handle_message(message),
+                // Keep the first reason, hanging up ourselves also ends the reader
+                ConnectionEvent::Disconnected(reason) => {
+                    if !matches!(self.status, NetStatus::Disconnected(_)) {
+                        self.status = NetStatus::Disconnected(reason);
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_message(&mut self, message: NetMessage) {
+        match message {
+            NetMessage::Hello { version, name } => {
+                if version != PROTOCOL_VERSION {
+                    self.connection.close();
+                    self.status = NetStatus::Disconnected(format!(
+                        "Protocol version mismatch (theirs v{}, ours v{})",
+                        version, PROTOCOL_VERSION
+                    ));
+                    return;
+                }
+                self.opponent_name = Some(name);
+                if self.is_host {
+                    self.start_round();
+                } else {
+                    self.status = NetStatus::WaitingForStart;
+                }
+            }
+            NetMessage::Start { seed, randomizer } => self.begin(seed, randomizer),
+            NetMessage::Snapshot(snapshot) => self.opponent = Some(snapshot),
+            NetMessage::Attack { lines } => {
+                if self.status == NetStatus::Playing {
+                    self.game.engine.receive_garbage(lines);
+                }
+            }
+            NetMessage::GameOver => match self.status {
+                NetStatus::Playing => {
+                    self.status = NetStatus::Won;
+                    self.game.set_state(GameState::GameOver);
+                }
+                // The opponent stops playing once it hears of our loss, so a
+                // GameOver after ours was sent before it arrived
+                NetStatus::Lost => self.status = NetStatus::Draw,
+                _ => {}
+            },
+        }
+    }
+
+    // Host only: deal a new game to both sides
+    fn start_round(&mut self) {
+        let seed = rand::random();
+        let randomizer = self.game.options.randomizer;
+        self.send(NetMessage::Start { seed, randomizer });
+        self.begin(seed, randomizer);
+    }
+
+    fn begin(&mut self, seed: u64, randomizer: RandomizerKind) {
+        self.game.options.randomizer = randomizer;
+        self.game.set_mode(GameMode::Versus);
+        self.game.start_game_with_seed(seed);
+        self.game.engine.board.cell_size = NET_VERSUS_CELL_SIZE;
+        self.opponent = None;
+        self.last_snapshot_frame = None;
+        self.status = NetStatus::Playing;
+    }
+
+    // Board with the falling piece drawn in, plus what the opponent shows beside it
+    fn snapshot(&self) -> BoardSnapshot {
+        let engine = &self.game.engine;
+        let mut cells = engine.board.to_ids();
+        if let Some(piece) = &engine.current_piece {
+            for (row, col) in piece.get_blocks() {
+                if row >= 0 && col >= 0 && (row as usize) < Board::get_height() && (col as usize) < Board::get_width() {
+                    cells[row as usize * Board::get_width() + col as usize] = piece.get_id();
+                }
+            }
+        }
+        BoardSnapshot {
+            cells,
+            score: engine.get_score(),
+            lines_cleared: engine.get_lines_cleared(),
+            incoming_garbage: engine.get_incoming_garbage(),
+        }
+    }
+
+    fn send(&mut self, message: NetMessage) {
+        if let Err(e) = self.connection.send(&message) {
+            self.status = NetStatus::Disconnected(e.to_string());
+        }
+    }
+
+    pub fn get_status(&self) -> &NetStatus {
+        &self.status
+    }
+
+    // Only the host deals games, the other side waits for the next Start
+    pub fn can_rematch(&self) -> bool {
+        self.is_host && matches!(self.status, NetStatus::Won | NetStatus::Lost | NetStatus::Draw)
+    }
+
+    pub fn rematch(&mut self) {
+        if self.can_rematch() {
+            self.start_round();
+        }
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.is_host
+    }
+
+    pub fn peer_address(&self) -> String {
+        self.connection.peer_address()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classes::network::{self, PendingConnection};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    fn wait_for<T>(mut poll: impl FnMut() -> Option<T>) -> T {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            if let Some(value) = poll() {
+                return value;
+            }
+            assert!(Instant::now() < deadline, "timed out on the loopback connection");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    // A host and a client connected over 127.0.0.1 on a free port
+    fn loopback() -> (Connection, Connection) {
+        let hosting = network::host(0).unwrap();
+        let port = match &hosting {
+            PendingConnection::Hosting(listener) => listener.local_addr().unwrap().port(),
+            PendingConnection::Joining(_) => unreachable!(),
+        };
+        let joining = network::join(format!("127.0.0.1:{}", port));
+        let host = wait_for(|| hosting.poll()).unwrap();
+        let client = wait_for(|| joining.poll()).unwrap();
+        (host, client)
+    }
+
+    fn pump_until(net_match: &mut NetVersusMatch, status: impl Fn(&NetStatus) -> bool) {
+        wait_for(|| {
+            net_match.receive();
+            status(&net_match.status).then_some(())
+        });
+    }
+
+    fn connected_matches() -> (NetVersusMatch, NetVersusMatch) {
+        let (host, client) = loopback();
+        let options = GameOptions::default();
+        let mut host = NetVersusMatch::new(&options, "Host", host, true);
+        let mut client = NetVersusMatch::new(&options, "Client", client, false);
+
+        pump_until(&mut host, |status| *status == NetStatus::Playing);
+        pump_until(&mut client, |status| *status == NetStatus::Playing);
+        (host, client)
+    }
+
+    #[test]
+    fn a_round_runs_from_hello_to_game_over() {
+        let (mut host, mut client) = connected_matches();
+        assert_eq!(host.opponent_name.as_deref(), Some("Client"));
+        assert_eq!(client.opponent_name.as_deref(), Some("Host"));
+        // Both sides deal the same queue from the host's seed
+        assert_eq!(host.game.engine.queue.get_seed(), client.game.engine.queue.get_seed());
+
+        host.send(NetMessage::Attack { lines: 3 });
+        wait_for(|| {
+            client.receive();
+            (client.game.engine.get_incoming_garbage() == 3).then_some(())
+        });
+
+        client.top_out();
+        assert_eq!(client.status, NetStatus::Lost);
+        pump_until(&mut host, |status| *status == NetStatus::Won);
+        assert!(host.can_rematch());
+        assert!(!client.can_rematch());
+    }
+
+    #[test]
+    fn crossing_game_overs_are_a_draw() {
+        let (mut host, mut client) = connected_matches();
+
+        // Both top out before either hears about the other
+        host.top_out();
+        client.top_out();
+
+        pump_until(&mut host, |status| *status == NetStatus::Draw);
+        pump_until(&mut client, |status| *status == NetStatus::Draw);
+    }
+
+    #[test]
+    fn a_version_mismatch_hangs_up() {
+        let (host, client) = loopback();
+        let mut client = NetVersusMatch::new(&GameOptions::default(), "Client", client, false);
+        host.send(&NetMessage::Hello { version: PROTOCOL_VERSION + 1, name: "Host".to_string() }).unwrap();
+
+        pump_until(&mut client, |status| matches!(status, NetStatus::Disconnected(_)));
+        assert_eq!(
+            client.status,
+            NetStatus::Disconnected(format!(
+                "Protocol version mismatch (theirs v{}, ours v{})",
+                PROTOCOL_VERSION + 1, PROTOCOL_VERSION
+            ))
+        );
+        assert!(client.opponent_name.is_none());
+
+        // The host hears the client's Hello and then the hang-up
+        let mut events = Vec::new();
+        wait_for(|| {
+            let event = host.poll()?;
+            let disconnected = matches!(event, ConnectionEvent::Disconnected(_));
+            events.push(event);
+            disconnected.then_some(())
+        });
+        assert!(matches!(events[0], ConnectionEvent::Message(NetMessage::Hello { version: PROTOCOL_VERSION, .. })));
+    }
+}
@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+
+use super::randomizer::RandomizerKind;
+
+// LAN versus protocol. Every message is one line of JSON tagged with its
+// "type". Both sides open with Hello and drop the connection if the versions
+// differ, after that the host deals the game with Start and both sides stream
+// snapshots of their board plus attacks until one of them sends GameOver.
+// Bump the version whenever a message changes shape.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+pub const DEFAULT_PORT: u16 = 7878;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum NetMessage {
+    Hello { version: u32, name: String },
+    // Sent by the host to start a game, both sides deal from the same seed
+    Start { seed: u64, randomizer: RandomizerKind },
+    Snapshot(BoardSnapshot),
+    // Garbage lines for the receiver, already reduced by the sender's cancelling
+    Attack { lines: u32 },
+    // The sender topped out
+    GameOver,
+}
+
+// What the opponent needs to draw the sender's side
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoardSnapshot {
+    // Board cell ids row by row, with the falling piece drawn in
+    pub cells: Vec<u8>,
+    pub score: u32,
+    pub lines_cleared: u32,
+    pub incoming_garbage: u32,
+}
+
+pub enum ConnectionEvent {
+    Message(NetMessage),
+    Disconnected(String),
+}
+
+// An open connection to the other player. Lines are read and written on
+// background threads so neither polling nor sending blocks the frame.
+pub struct Connection {
+    stream: TcpStream,
+    events: Receiver<ConnectionEvent>,
+    outgoing: Sender<String>,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        let reader = BufReader::new(stream.try_clone()?);
+        let mut writer = stream.try_clone()?;
+        let (sender, events) = mpsc::channel();
+        let (outgoing, lines) = mpsc::channel::<String>();
+
+        // Stops once the Connection is dropped and nothing is left to send
+        let write_errors = sender.clone();
+        thread::spawn(move || {
+            for line in lines {
+                if let Err(e) = writer.write_all(line.as_bytes()) {
+                    let _ = write_errors.send(ConnectionEvent::Disconnected(e.to_string()));
+                    return;
+                }
+            }
+        });
+
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let event = match line {
+                    Ok(line) => match serde_json::from_str(&line) {
+                        Ok(message) => ConnectionEvent::Message(message),
+                        Err(e) => ConnectionEvent::Disconnected(format!("Bad message: {}", e)),
+                    },
+                    Err(e) => ConnectionEvent::Disconnected(e.to_string()),
+                };
+                let stop = matches!(event, ConnectionEvent::Disconnected(_));
+                if sender.send(event).is_err() || stop {
+                    return;
+                }
+            }
+            let _ = sender.send(ConnectionEvent::Disconnected("Connection closed".to_string()));
+        });
+
+        Ok(Self { stream, events, outgoing })
+    }
+
+    // Queue a message for the writer thread, write errors come back through poll
+    pub fn send(&self, message: &NetMessage) -> io::Result<()> {
+        let mut line = serde_json::to_string(message)?;
+        line.push('\n');
+        self.outgoing.send(line).map_err(|_| io::Error::other("Connection closed"))
+    }
+
+    // Hang up, the reader and writer threads stop on their own
+    pub fn close(&self) {
+        let _ = self.stream.shutdown(std::net::Shutdown::Both);
+    }
+
+    // Next event received since the last poll, if any. The reader thread
+    // reports the disconnect itself before it stops.
+    pub fn poll(&self) -> Option<ConnectionEvent> {
+        self.events.try_recv().ok()
+    }
+
+    pub fn peer_address(&self) -> String {
+        self.stream.peer_addr().map(|addr| addr.to_string()).unwrap_or_default()
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+// A host waiting for someone to join, or a join still connecting
+pub enum PendingConnection {
+    Hosting(TcpListener),
+    Joining(Receiver<io::Result<TcpStream>>),
+}
+
+impl PendingConnection {
+    pub fn is_host(&self) -> bool {
+        matches!(self, PendingConnection::Hosting(_))
+    }
+
+    // The connection once the other player is there
+    pub fn poll(&self) -> Option<io::Result<Connection>> {
+        let stream = match self {
+            PendingConnection::Hosting(listener) => match listener.accept() {
+                Ok((stream, _)) => stream.set_nonblocking(false).map(|_| stream),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return None,
+                Err(e) => Err(e),
+            },
+            PendingConnection::Joining(result) => match result.try_recv() {
+                Ok(stream) => stream,
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => Err(io::Error::other("Connection attempt stopped")),
+            },
+        };
+        Some(stream.and_then(Connection::new))
+    }
+}
+
+// Listen on `port` for the other player. Accepting is polled every frame so
+// hosting can be cancelled, and dropping the listener frees the port.
+pub fn host(port: u16) -> io::Result<PendingConnection> {
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    listener.set_nonblocking(true)?;
+    Ok(PendingConnection::Hosting(listener))
+}
+
+// Connect to a host at `address` (host:port) without blocking the frame
+pub fn join(address: String) -> PendingConnection {
+    let (sender, result) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(TcpStream::connect(address));
+    });
+    PendingConnection::Joining(result)
+}
@@ -0,0 +1,359 @@
+use super::board::Board;
+use super::finesse::FinesseResult;
+use super::piece::Piece;
+use serde::{Deserialize, Serialize};
+
+// One placement of an opener: the piece, how many times it's turned clockwise
+// from spawn and the leftmost column it covers. Every piece is hard dropped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OpenerStep {
+    pub piece: &'static str,
+    pub rotation: u8,
+    pub column: i32,
+}
+
+const fn step(piece: &'static str, rotation: u8, column: i32) -> OpenerStep {
+    OpenerStep { piece, rotation, column }
+}
+
+// I flat in the bottom right, and a Z on the left roofing a T-spin double
+// slot for the T that comes last in the bag
+//   ..Z.....LL
+//   .ZZ....OOL
+//   JZ...SSOOL
+//   JJJ.SSIIII
+const TKI: [OpenerStep; 6] = [
+    step("I", 0, 6),
+    step("O", 0, 7),
+    step("S", 0, 4),
+    step("J", 0, 0),
+    step("Z", 1, 1),
+    step("L", 3, 8),
+];
+
+// A flat first bag with a well down the left, while its T waits in hold. The
+// second bag stands an I in the well and builds a T-spin double slot on the
+// right, then the held T spins into it and the J on the left roofs a T-spin
+// triple for the second bag's T
+//   JJ......S.
+//   J.......SS
+//   JLLLZZ...S
+//   ILOOJZZ.OO
+//   I.OOJSZZOO
+//   I..JJSSZZL
+//   I.IIIISLLL
+const DT_CANNON: [OpenerStep; 12] = [
+    step("I", 0, 2),
+    step("S", 1, 5),
+    step("L", 0, 7),
+    step("J", 3, 3),
+    step("Z", 0, 6),
+    step("O", 0, 2),
+    step("I", 1, 0),
+    step("O", 0, 8),
+    step("L", 2, 1),
+    step("Z", 0, 4),
+    step("J", 1, 0),
+    step("S", 1, 8),
+];
+
+// The first bag's T stands against the left wall under an O, leaving an
+// overhang with a chimney beside it. The second bag's T drops down the
+// chimney and kicks in under the O, ending upright in a T-spin double
+//   .OO..S.I..
+//   TOO..SSILL
+//   TT...SSILL
+//   T..ZZSSILL
+//   OO..ZZSJLL
+//   OO.IIIIJJJ
+const STSD: [OpenerStep; 11] = [
+    step("O", 0, 0),
+    step("I", 0, 3),
+    step("J", 0, 7),
+    step("Z", 0, 3),
+    step("S", 1, 5),
+    step("L", 1, 8),
+    step("T", 1, 0),
+    step("I", 1, 7),
+    step("L", 3, 8),
+    step("S", 1, 5),
+    step("O", 0, 1),
+];
+
+// Six pieces stacked flat on the left, leaving a 4 wide well the T and the
+// next bag clear all 4 rows with
+//   LLLJJJ....
+//   LZOOSJ....
+//   ZZOOSS....
+//   ZIIIIS....
+const PCO: [OpenerStep; 6] = [
+    step("I", 0, 1),
+    step("O", 0, 2),
+    step("S", 1, 4),
+    step("Z", 1, 0),
+    step("J", 2, 3),
+    step("L", 2, 0),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Opener {
+    #[default]
+    Tki,
+    DtCannon,
+    Stsd,
+    Pco,
+}
+
+impl Opener {
+    pub const ALL: [Opener; 4] = [Opener::Tki, Opener::DtCannon, Opener::Stsd, Opener::Pco];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Opener::Tki => "TKI",
+            Opener::DtCannon => "DT Cannon",
+            Opener::Stsd => "STSD",
+            Opener::Pco => "PCO",
+        }
+    }
+
+    pub fn steps(&self) -> &'static [OpenerStep] {
+        match self {
+            Opener::Tki => &TKI,
+            Opener::DtCannon => &DT_CANNON,
+            Opener::Stsd => &STSD,
+            Opener::Pco => &PCO,
+        }
+    }
+
+    // Pieces in the order the steps place them, the order the queue deals them
+    pub fn pieces(&self) -> Vec<&'static str> {
+        self.steps().iter().map(|step| step.piece).collect()
+    }
+}
+
+impl OpenerStep {
+    // Cells the piece covers once hard dropped in place on `board`, None if
+    // the stack is in the way
+    pub fn target_blocks(&self, board: &Board) -> Option<Vec<(i32, i32)>> {
+        let mut piece = Piece::create_piece(self.piece);
+        for _ in 0..self.rotation {
+            piece.rotate_clockwise();
+        }
+
+        let blocks = piece.get_blocks();
+        let left = blocks.iter().map(|(_, col)| *col).min()?;
+        let top = blocks.iter().map(|(row, _)| *row).min()?;
+        let mut blocks: Vec<(i32, i32)> = blocks
+            .iter()
+            .map(|(row, col)| (row - top, col - left + self.column))
+            .collect();
+        if !board.is_valid_position(&blocks) {
+            return None;
+        }
+
+        loop {
+            let lower: Vec<(i32, i32)> = blocks.iter().map(|(row, col)| (row + 1, *col)).collect();
+            if !board.is_valid_position(&lower) {
+                break;
+            }
+            blocks = lower;
+        }
+        blocks.sort();
+        Some(blocks)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlacementGrade {
+    // Right spot with the fewest inputs
+    Perfect,
+    // Right spot, but with more inputs than needed
+    Good,
+    Miss,
+}
+
+impl PlacementGrade {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            PlacementGrade::Perfect => "Perfect",
+            PlacementGrade::Good => "Good",
+            PlacementGrade::Miss => "Miss",
+        }
+    }
+
+    // Compare where a piece locked with the target. Placements finesse can't
+    // judge get the benefit of the doubt.
+    pub fn grade(placed: &[(i32, i32)], target: Option<&[(i32, i32)]>, finesse: Option<FinesseResult>) -> Self {
+        let mut placed = placed.to_vec();
+        placed.sort();
+        if target != Some(placed.as_slice()) {
+            PlacementGrade::Miss
+        } else if finesse.is_some_and(|result| result.is_fault()) {
+            PlacementGrade::Good
+        } else {
+            PlacementGrade::Perfect
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classes::engine::{Engine, Handling};
+    use crate::classes::perfect_clear::{self, PcHint};
+    use crate::classes::queue::Queue;
+    use crate::classes::randomizer::RandomizerKind;
+    use crate::enums::game_actions::GameAction;
+    use crate::enums::game_modes::GameMode;
+    use std::collections::{HashSet, VecDeque};
+
+    fn board_after(steps: &[OpenerStep]) -> Board {
+        let mut board = Board::new();
+        for (i, step) in steps.iter().enumerate() {
+            let piece = Piece::create_piece(step.piece);
+            let blocks = step.target_blocks(&board).unwrap_or_else(|| panic!("step {} doesn't fit", i));
+            for (row, col) in blocks {
+                board.set_cell(row as usize, col as usize, piece.get_id());
+            }
+            assert_eq!(board.clear_lines(), 0, "step {} clears a line", i);
+        }
+        board
+    }
+
+    // Board left by a T spun in from spawn on `board` that the engine scores
+    // as a T-spin clearing `lines`, if there's one
+    fn t_spin(board: &Board, lines: u32) -> Option<Board> {
+        let spawn = Piece::create_piece("T");
+        let key = |piece: &Piece| (piece.get_position(), piece.get_rotation());
+        let mut seen = HashSet::from([key(&spawn)]);
+        let mut queue = VecDeque::from([spawn]);
+        while let Some(piece) = queue.pop_front() {
+            for clockwise in [true, false] {
+                let mut spun = piece.clone();
+                if spun.rotate(clockwise, board).is_none() {
+                    continue;
+                }
+                let mut below = spun.clone();
+                below.move_down();
+                if !board.is_valid_position(&below.get_blocks()) {
+                    let queue = Queue::with_seed(0).with_preset(vec!["T".to_string()]);
+                    let mut engine = Engine::with_queue(Handling::default(), queue).with_starting_board(Some(board.to_ids()));
+                    engine.current_piece = Some(piece.clone());
+                    engine.press(if clockwise { GameAction::RotateCW } else { GameAction::RotateCCW });
+                    engine.press(GameAction::HardDrop);
+                    let stats = engine.get_game_stats();
+                    if engine.get_lines_cleared() == lines && stats.t_spin_doubles + stats.t_spin_triples == 1 {
+                        return Some(engine.board.clone());
+                    }
+                }
+            }
+
+            let mut moves = [piece.clone(), piece.clone(), piece.clone(), piece.clone(), piece.clone()];
+            moves[0].move_left();
+            moves[1].move_right();
+            moves[2].move_down();
+            moves[3].rotate(true, board);
+            moves[4].rotate(false, board);
+            for next in moves {
+                if board.is_valid_position(&next.get_blocks()) && seen.insert(key(&next)) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn every_opener_fits_without_clearing_lines() {
+        for opener in Opener::ALL {
+            board_after(opener.steps());
+        }
+    }
+
+    #[test]
+    fn tki_leaves_a_t_spin_double() {
+        assert!(t_spin(&board_after(Opener::Tki.steps()), 2).is_some());
+    }
+
+    #[test]
+    fn dt_cannon_leaves_a_t_spin_double_then_a_triple() {
+        let after_double = t_spin(&board_after(Opener::DtCannon.steps()), 2).unwrap();
+        assert!(t_spin(&after_double, 3).is_some());
+    }
+
+    #[test]
+    fn stsd_leaves_an_upright_t_spin_double() {
+        let board = board_after(Opener::Stsd.steps());
+        let after_double = t_spin(&board, 2).unwrap();
+        // Only the head of the upright T is left, dropped into the bottom row
+        // over the column its stem went down
+        assert_eq!(after_double.get_cell(21, 2), Piece::create_piece("T").get_id());
+        assert!(!after_double.is_occupied(20, 2));
+    }
+
+    #[test]
+    fn pco_perfect_clears_with_the_next_bag() {
+        let pieces: Vec<String> = ["T", "I", "L", "J", "S", "Z", "O", "T"].iter().map(|name| name.to_string()).collect();
+        let hint = perfect_clear::solve(&board_after(Opener::Pco.steps()), 4, &pieces, None, true, &|| false);
+        assert!(matches!(hint, PcHint::Possible(_)));
+    }
+
+    // Hold anything the opener saves for later, then turn, shift and drop
+    // each piece onto its target
+    fn play_targets(engine: &mut Engine) {
+        while let Some(step) = engine.get_opener_step() {
+            if engine.current_piece.as_ref().unwrap().get_name() != step.piece {
+                engine.press(GameAction::HoldPiece);
+            }
+            let turns: &[GameAction] = match step.rotation {
+                1 => &[GameAction::RotateCW],
+                2 => &[GameAction::RotateCW, GameAction::RotateCW],
+                3 => &[GameAction::RotateCCW],
+                _ => &[],
+            };
+            for turn in turns {
+                engine.press(*turn);
+            }
+            loop {
+                let left = engine.current_piece.as_ref().unwrap().get_blocks().iter().map(|(_, col)| *col).min().unwrap();
+                let shift = match left.cmp(&step.column) {
+                    std::cmp::Ordering::Less => GameAction::MoveRight,
+                    std::cmp::Ordering::Greater => GameAction::MoveLeft,
+                    std::cmp::Ordering::Equal => break,
+                };
+                engine.press(shift);
+                engine.release(shift);
+            }
+            engine.press(GameAction::HardDrop);
+            engine.tick();
+        }
+    }
+
+    #[test]
+    fn dropping_every_piece_on_its_target_finishes_the_opener() {
+        for opener in Opener::ALL {
+            for seed in 0..4 {
+                let queue = Queue::with_randomizer(RandomizerKind::SevenBag, seed).with_bags(&opener.pieces());
+                let mut engine = Engine::with_mode(Handling::default(), queue, GameMode::Opener).with_opener(Some(opener));
+                play_targets(&mut engine);
+
+                assert!(engine.is_completed(), "{} with seed {}", opener.display_name(), seed);
+                let grades = engine.get_opener_grades();
+                assert_eq!(grades.len(), opener.steps().len());
+                assert!(!grades.contains(&PlacementGrade::Miss));
+            }
+        }
+    }
+
+    #[test]
+    fn a_piece_off_target_misses_and_ends_the_run() {
+        let queue = Queue::with_seed(0).with_bags(&Opener::Tki.pieces());
+        let mut engine = Engine::with_mode(Handling::default(), queue, GameMode::Opener).with_opener(Some(Opener::Tki));
+        engine.press(GameAction::MoveLeft);
+        engine.press(GameAction::HardDrop);
+
+        assert_eq!(engine.get_opener_grades(), [PlacementGrade::Miss]);
+        assert!(engine.is_game_over());
+        assert_eq!(engine.get_opener_target(), None);
+    }
+}
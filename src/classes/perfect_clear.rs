@@ -0,0 +1,597 @@
+use super::board::{Board, GARBAGE_ID};
+use super::piece::Piece;
+use super::randomizer::PIECE_NAMES;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+
+// Rows a perfect clear is built in, the stack may never reach above them
+pub const PC_HEIGHT: usize = 4;
+
+// Next pieces shown in PC practice, enough to see a whole clear from an empty board
+pub const PC_PREVIEW: usize = 10;
+
+// Positions each solver pass looks at before giving up on an answer
+const SEARCH_BUDGET: usize = 1_000_000;
+
+// Tallest search, rows pack 10 bits each into a u64 to remember failed positions
+const MAX_SEARCH_HEIGHT: usize = 6;
+
+// Positions between checks for a search that's no longer wanted
+const CANCEL_CHECK_INTERVAL: usize = 1024;
+
+const FULL_ROW: u16 = (1 << 10) - 1;
+
+// Boards PC practice can start from. Every setup leaves a multiple of 4 cells
+// open in the bottom rows so whole pieces can fill them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum PcSetup {
+    #[default]
+    Empty,
+    LeftStairs,
+    RightStairs,
+    Valley,
+}
+
+impl PcSetup {
+    pub const ALL: [PcSetup; 4] = [
+        PcSetup::Empty,
+        PcSetup::LeftStairs,
+        PcSetup::RightStairs,
+        PcSetup::Valley,
+    ];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            PcSetup::Empty => "Empty Board",
+            PcSetup::LeftStairs => "Left Stairs",
+            PcSetup::RightStairs => "Right Stairs",
+            PcSetup::Valley => "Valley",
+        }
+    }
+
+    // Bottom rows of the setup from the top down, '#' is a filled cell
+    fn rows(&self) -> &'static [&'static str] {
+        match self {
+            PcSetup::Empty => &[],
+            PcSetup::LeftStairs => &[
+                "###.......",
+                "####......",
+                "####......",
+                "#####.....",
+            ],
+            PcSetup::RightStairs => &[
+                ".......###",
+                "......####",
+                "......####",
+                ".....#####",
+            ],
+            PcSetup::Valley => &[
+                "#.........",
+                "###......#",
+                "####...###",
+            ],
+        }
+    }
+
+    // Starting cells in the form Board::from_ids reads
+    pub fn board_ids(&self) -> Vec<u8> {
+        let mut board = Board::new();
+        let rows = self.rows();
+        let top = Board::get_height() - rows.len();
+        for (i, row) in rows.iter().enumerate() {
+            for (col, cell) in row.chars().enumerate() {
+                if cell == '#' {
+                    board.set_cell(top + i, col, GARBAGE_ID);
+                }
+            }
+        }
+        board.to_ids()
+    }
+}
+
+// One placement of a solution
+#[derive(Debug, Clone, PartialEq)]
+pub struct PcStep {
+    pub piece: String,
+    // Whether hold is pressed before placing the piece
+    pub hold: bool,
+    // Where the piece lands, on the board as it is when the step is played
+    pub blocks: Vec<(i32, i32)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PcHint {
+    Possible(Vec<PcStep>),
+    Impossible,
+    // The search ran out of budget before finding either answer
+    Unknown,
+}
+
+// Look for a perfect clear inside the bottom `height` rows using the current
+// piece, the hold piece and the pieces after it in `pieces`, current first.
+// Pieces are hard dropped straight down from above the stack, which is how
+// every spot can be reached while nothing sticks out above those rows.
+// The search gives up with Unknown once `cancelled` returns true.
+pub fn solve(
+    board: &Board,
+    height: usize,
+    pieces: &[String],
+    held: Option<&str>,
+    can_hold: bool,
+    cancelled: &dyn Fn() -> bool,
+) -> PcHint {
+    if height > MAX_SEARCH_HEIGHT {
+        return PcHint::Unknown;
+    }
+    let board_height = Board::get_height();
+    if height == 0 || (0..board_height - height).any(|row| !row_is_empty(board, row)) {
+        return PcHint::Impossible;
+    }
+
+    // Row bits from the bottom up
+    let rows: Vec<u16> = (0..height)
+        .map(|i| {
+            (0..Board::get_width())
+                .filter(|col| board.get_cell(board_height - 1 - i, *col) != 0)
+                .fold(0, |bits, col| bits | 1 << col)
+        })
+        .collect();
+    let open: usize = rows.iter().map(|row| 10 - row.count_ones() as usize).sum();
+    if !open.is_multiple_of(4) {
+        return PcHint::Impossible;
+    }
+
+    // A quick pass skips positions that leave an open area whole pieces can't
+    // fill, which finds most clears fast. Clearing a line between two areas can
+    // join them though, so only the full pass can rule a clear out.
+    let pieces: Vec<usize> = pieces.iter().filter_map(|name| piece_index(name)).collect();
+    let held = held.and_then(piece_index);
+    let mut quick = Search::new(pieces.clone(), can_hold, true, cancelled);
+    if quick.run(&rows, 0, held) == Some(true) {
+        return quick.solution();
+    }
+    let mut full = Search::new(pieces, can_hold, false, cancelled);
+    match full.run(&rows, 0, held) {
+        Some(true) => full.solution(),
+        Some(false) => PcHint::Impossible,
+        None => PcHint::Unknown,
+    }
+}
+
+struct PcJob {
+    generation: u64,
+    board: Board,
+    height: usize,
+    pieces: Vec<String>,
+    held: Option<String>,
+    can_hold: bool,
+}
+
+// Runs `solve` on one worker thread. Each request bumps the generation, which
+// stops the search in progress and keeps its answer from being delivered.
+pub struct PcSolver {
+    jobs: Sender<PcJob>,
+    hints: Receiver<(u64, PcHint)>,
+    generation: Arc<AtomicU64>,
+}
+
+impl PcSolver {
+    pub fn new() -> Self {
+        let (jobs, job_queue) = mpsc::channel::<PcJob>();
+        let (hint_sender, hints) = mpsc::channel();
+        let generation = Arc::new(AtomicU64::new(0));
+        let current = Arc::clone(&generation);
+
+        // Stops once the PcSolver is dropped
+        thread::spawn(move || {
+            while let Ok(mut job) = job_queue.recv() {
+                // Only the latest request is worth solving
+                while let Ok(newer) = job_queue.try_recv() {
+                    job = newer;
+                }
+                let stale = || current.load(Ordering::Relaxed) != job.generation;
+                if stale() {
+                    continue;
+                }
+                let hint = solve(&job.board, job.height, &job.pieces, job.held.as_deref(), job.can_hold, &stale);
+                if !stale() && hint_sender.send((job.generation, hint)).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Self { jobs, hints, generation }
+    }
+
+    // Start solving this position, dropping whatever was asked before
+    pub fn request(&self, board: Board, height: usize, pieces: Vec<String>, held: Option<String>, can_hold: bool) {
+        let generation = self.generation.fetch_add(1, Ordering::Relaxed) + 1;
+        let _ = self.jobs.send(PcJob { generation, board, height, pieces, held, can_hold });
+    }
+
+    pub fn cancel(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // The answer for the latest request, once it's ready
+    pub fn poll(&self) -> Option<PcHint> {
+        let generation = self.generation.load(Ordering::Relaxed);
+        self.hints
+            .try_iter()
+            .find(|(answered, _)| *answered == generation)
+            .map(|(_, hint)| hint)
+    }
+}
+
+impl Drop for PcSolver {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+fn row_is_empty(board: &Board, row: usize) -> bool {
+    (0..Board::get_width()).all(|col| board.get_cell(row, col) == 0)
+}
+
+fn piece_index(name: &str) -> Option<usize> {
+    PIECE_NAMES.iter().position(|piece| *piece == name)
+}
+
+// A piece orientation as (rows up from its lowest cell, columns right of its leftmost cell)
+type Shape = Vec<(usize, usize)>;
+
+// The distinct orientations of a piece, the ones covering the same cells only once
+fn shapes(name: &str) -> Vec<Shape> {
+    let mut piece = Piece::create_piece(name);
+    let mut shapes: Vec<Shape> = Vec::new();
+    for _ in 0..4 {
+        let blocks = piece.get_blocks();
+        let bottom = blocks.iter().map(|(row, _)| *row).max().unwrap_or(0);
+        let left = blocks.iter().map(|(_, col)| *col).min().unwrap_or(0);
+        let mut shape: Shape = blocks
+            .iter()
+            .map(|(row, col)| ((bottom - row) as usize, (col - left) as usize))
+            .collect();
+        shape.sort();
+        if !shapes.contains(&shape) {
+            shapes.push(shape);
+        }
+        piece.rotate_clockwise();
+    }
+    shapes
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Step {
+    piece: usize,
+    hold: bool,
+    shape: usize,
+    // Column of the shape's left edge and row of its bottom, counted from the floor
+    x: usize,
+    y: usize,
+}
+
+impl Step {
+    fn to_pc_step(self, shapes: &[Vec<Shape>; 7]) -> PcStep {
+        let floor = Board::get_height() as i32 - 1;
+        PcStep {
+            piece: PIECE_NAMES[self.piece].to_string(),
+            hold: self.hold,
+            blocks: shapes[self.piece][self.shape]
+                .iter()
+                .map(|(up, right)| (floor - (self.y + up) as i32, (self.x + right) as i32))
+                .collect(),
+        }
+    }
+}
+
+// Depth first search over placements. Positions already shown to fail are
+// remembered by their rows, how far into the pieces they are and the hold piece.
+struct Search<'a> {
+    pieces: Vec<usize>,
+    can_hold: bool,
+    shapes: [Vec<Shape>; 7],
+    // Skip positions with an open area that isn't a multiple of 4 cells
+    quick: bool,
+    failed: HashSet<(u64, usize, usize, Option<usize>)>,
+    nodes: usize,
+    path: Vec<Step>,
+    cancelled: &'a dyn Fn() -> bool,
+}
+
+impl<'a> Search<'a> {
+    fn new(pieces: Vec<usize>, can_hold: bool, quick: bool, cancelled: &'a dyn Fn() -> bool) -> Self {
+        Self {
+            pieces,
+            can_hold,
+            shapes: PIECE_NAMES.map(shapes),
+            quick,
+            failed: HashSet::new(),
+            nodes: 0,
+            path: Vec::new(),
+            cancelled,
+        }
+    }
+
+    fn solution(&self) -> PcHint {
+        PcHint::Possible(self.path.iter().map(|step| step.to_pc_step(&self.shapes)).collect())
+    }
+
+    // Some(true) once a perfect clear is in `path`, None when out of budget
+    fn run(&mut self, rows: &[u16], index: usize, hold: Option<usize>) -> Option<bool> {
+        let open: usize = rows.iter().map(|row| 10 - row.count_ones() as usize).sum();
+        let available = self.pieces.len().saturating_sub(index) + hold.is_some() as usize;
+        if open / 4 > available {
+            return Some(false);
+        }
+
+        if self.quick && !areas_fillable(rows) {
+            return Some(false);
+        }
+        let key = (pack(rows), rows.len(), index, hold);
+        if self.failed.contains(&key) {
+            return Some(false);
+        }
+        self.nodes += 1;
+        if self.nodes > SEARCH_BUDGET
+            || (self.nodes.is_multiple_of(CANCEL_CHECK_INTERVAL) && (self.cancelled)())
+        {
+            return None;
+        }
+
+        // Play the next piece, or press hold and play the held one (or the one
+        // after). Past the last piece known, holding can still bring out the held one.
+        let next = self.pieces.get(index).copied();
+        let mut choices = Vec::new();
+        if let Some(piece) = next {
+            choices.push((piece, false, index + 1, hold));
+        }
+        if index > 0 || self.can_hold {
+            match (hold, next) {
+                (Some(held), _) if Some(held) != next => choices.push((held, true, index + 1, next)),
+                (None, Some(piece)) => {
+                    if let Some(&after) = self.pieces.get(index + 1) {
+                        choices.push((after, true, index + 2, Some(piece)));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Lowest placements first, filling from the floor up finds clears soonest
+        let mut placements = Vec::new();
+        for (piece, used_hold, next_index, next_hold) in choices {
+            for (shape, cells) in self.shapes[piece].iter().enumerate() {
+                let width = cells.iter().map(|(_, right)| right + 1).max().unwrap_or(1);
+                for x in 0..=10 - width {
+                    if let Some((y, after, cleared)) = drop(rows, cells, x) {
+                        let step = Step { piece, hold: used_hold, shape, x, y };
+                        placements.push((step, after, cleared, next_index, next_hold));
+                    }
+                }
+            }
+        }
+        placements.sort_by_key(|(step, ..)| step.y);
+
+        for (step, after, cleared, next_index, next_hold) in placements {
+            self.path.push(step);
+            if cleared > 0 && after.iter().all(|row| *row == 0) {
+                return Some(true);
+            }
+            match self.run(&after, next_index, next_hold) {
+                Some(true) => return Some(true),
+                None => return None,
+                Some(false) => {}
+            }
+            self.path.pop();
+        }
+
+        self.failed.insert(key);
+        Some(false)
+    }
+}
+
+// Hard drop a shape at column `x`. Gives the row it lands on, the rows left
+// after clearing and the lines cleared, or None if it sticks out.
+fn drop(rows: &[u16], shape: &Shape, x: usize) -> Option<(usize, Vec<u16>, usize)> {
+    let fits = |y: usize| {
+        shape.iter().all(|(up, right)| rows.get(y + up).is_none_or(|row| row & (1 << (x + right)) == 0))
+    };
+    let mut y = rows.len();
+    while y > 0 && fits(y - 1) {
+        y -= 1;
+    }
+    if shape.iter().any(|(up, _)| y + up >= rows.len()) {
+        return None;
+    }
+
+    let mut placed = rows.to_vec();
+    for (up, right) in shape {
+        placed[y + up] |= 1 << (x + right);
+    }
+    let after: Vec<u16> = placed.iter().copied().filter(|row| *row != FULL_ROW).collect();
+    let cleared = placed.len() - after.len();
+    Some((y, after, cleared))
+}
+
+// Whether every open area, walled in by the stack, the sides and the top of
+// the rows, has a multiple of 4 cells
+fn areas_fillable(rows: &[u16]) -> bool {
+    let mut unseen: Vec<u16> = rows.iter().map(|row| !row & FULL_ROW).collect();
+    for start in 0..rows.len() {
+        while unseen[start] != 0 {
+            // Flood fill from the lowest open cell left in the row
+            let mut area = vec![0; rows.len()];
+            area[start] = unseen[start] & unseen[start].wrapping_neg();
+            loop {
+                let grown: Vec<u16> = (0..rows.len())
+                    .map(|i| {
+                        let below = if i > 0 { area[i - 1] } else { 0 };
+                        let above = area.get(i + 1).copied().unwrap_or(0);
+                        (area[i] | area[i] << 1 | area[i] >> 1 | below | above) & unseen[i]
+                    })
+                    .collect();
+                if grown == area {
+                    break;
+                }
+                area = grown;
+            }
+
+            if !area.iter().map(|row| row.count_ones()).sum::<u32>().is_multiple_of(4) {
+                return false;
+            }
+            for (row, filled) in unseen.iter_mut().zip(&area) {
+                *row &= !filled;
+            }
+        }
+    }
+    true
+}
+
+fn pack(rows: &[u16]) -> u64 {
+    debug_assert!(rows.len() <= MAX_SEARCH_HEIGHT);
+    rows.iter().enumerate().fold(0, |bits, (i, row)| bits | (*row as u64) << (i * 10))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    // Board with `rows` at the bottom, '#' marks a filled cell
+    fn board(rows: &[&str]) -> Board {
+        let mut board = Board::new();
+        let top = Board::get_height() - rows.len();
+        for (i, row) in rows.iter().enumerate() {
+            for (col, cell) in row.chars().enumerate() {
+                if cell == '#' {
+                    board.set_cell(top + i, col, GARBAGE_ID);
+                }
+            }
+        }
+        board
+    }
+
+    fn names(pieces: &[&str]) -> Vec<String> {
+        pieces.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn never() -> bool {
+        false
+    }
+
+    // Play the steps the way the engine deals pieces, checking each one is
+    // the piece on hand and lands where a hard drop leaves it
+    fn replay(mut board: Board, pieces: &[String], mut held: Option<String>, steps: &[PcStep]) -> Board {
+        let mut next = pieces.iter().cloned();
+        for (i, step) in steps.iter().enumerate() {
+            let piece = if step.hold {
+                match held.take() {
+                    Some(held_piece) => {
+                        held = next.next();
+                        held_piece
+                    }
+                    None => {
+                        held = next.next();
+                        next.next().unwrap()
+                    }
+                }
+            } else {
+                next.next().unwrap()
+            };
+            assert_eq!(piece, step.piece, "step {}", i);
+
+            assert_eq!(step.blocks.len(), 4, "step {}", i);
+            assert!(board.is_valid_position(&step.blocks), "step {} overlaps the stack", i);
+            let below: Vec<_> = step.blocks.iter().map(|(row, col)| (row + 1, *col)).collect();
+            assert!(!board.is_valid_position(&below), "step {} is floating", i);
+
+            for (row, col) in &step.blocks {
+                board.set_cell(*row as usize, *col as usize, GARBAGE_ID);
+            }
+            board.clear_lines();
+        }
+        board
+    }
+
+    #[test]
+    fn a_solution_replays_to_an_empty_board() {
+        let start = board(&[
+            "######....",
+            "######....",
+            "######....",
+            "######....",
+        ]);
+        let pieces = names(&["L", "J", "I", "I"]);
+        let PcHint::Possible(steps) = solve(&start, PC_HEIGHT, &pieces, None, true, &never) else {
+            panic!("expected a perfect clear");
+        };
+        assert!(replay(start, &pieces, None, &steps).is_empty());
+    }
+
+    #[test]
+    fn a_well_only_an_i_fits_is_impossible_with_o_pieces() {
+        let well = board(&[
+            "#########.",
+            "#########.",
+            "#########.",
+            "#########.",
+        ]);
+        let pieces = names(&["O", "O", "O", "O"]);
+        assert_eq!(solve(&well, PC_HEIGHT, &pieces, None, true, &never), PcHint::Impossible);
+        // Cells that whole pieces can't add up to are ruled out straight away
+        assert_eq!(solve(&board(&["#######..."]), 1, &pieces, None, true, &never), PcHint::Impossible);
+    }
+
+    #[test]
+    fn a_solution_can_need_hold() {
+        let well = board(&[
+            "#########.",
+            "#########.",
+            "#########.",
+            "#########.",
+        ]);
+        let pieces = names(&["O", "I"]);
+        let PcHint::Possible(steps) = solve(&well, PC_HEIGHT, &pieces, None, true, &never) else {
+            panic!("expected a perfect clear through hold");
+        };
+        assert_eq!(steps.len(), 1);
+        assert!(steps[0].hold);
+        assert_eq!(steps[0].piece, "I");
+        assert!(replay(well.clone(), &pieces, None, &steps).is_empty());
+
+        // Having held already this piece, the O has to go in the well
+        assert_eq!(solve(&well, PC_HEIGHT, &pieces, None, false, &never), PcHint::Impossible);
+    }
+
+    #[test]
+    fn a_newer_request_drops_the_older_answer() {
+        let well = board(&[
+            "#########.",
+            "#########.",
+            "#########.",
+            "#########.",
+        ]);
+        let solver = PcSolver::new();
+        solver.request(well.clone(), PC_HEIGHT, names(&["O", "O", "O", "O"]), None, false);
+        solver.request(well, PC_HEIGHT, names(&["I"]), None, false);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let hint = loop {
+            if let Some(hint) = solver.poll() {
+                break hint;
+            }
+            assert!(Instant::now() < deadline, "the solver never answered");
+            thread::sleep(Duration::from_millis(1));
+        };
+        assert!(matches!(hint, PcHint::Possible(_)), "got {:?}", hint);
+
+        // Nothing else comes through, even if the first request was answered
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(solver.poll(), None);
+    }
+}
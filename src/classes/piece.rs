@@ -1,4 +1,5 @@
 use eframe::egui::Color32;
+use super::board::Board;
 
 // I piece uses 4x4 grid - 4 rotations
 const I_ROTATIONS: [[[u8; 4]; 4]; 4] = [
@@ -178,6 +179,44 @@ const L_ROTATIONS: [[[u8; 3]; 3]; 4] = [
     ],
 ];
 
+// SRS wall kick offsets as (x, y) with +x right and +y up, tried in order.
+// Rows follow the rotation transitions 0->R, R->0, R->2, 2->R, 2->L, L->2, L->0, 0->L
+const JLSTZ_KICKS: [[(i32, i32); 5]; 8] = [
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+];
+
+// The I piece has its own kick table
+const I_KICKS: [[(i32, i32); 5]; 8] = [
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+];
+
+fn kick_index(from: u8, to: u8) -> usize {
+    match (from, to) {
+        (0, 1) => 0,
+        (1, 0) => 1,
+        (1, 2) => 2,
+        (2, 1) => 3,
+        (2, 3) => 4,
+        (3, 2) => 5,
+        (3, 0) => 6,
+        _ => 7, // 0 -> 3
+    }
+}
+
 #[derive(Clone)]
 enum PieceShape {
     Large([[[u8; 4]; 4]; 4]),  // For I piece - 4 rotations
@@ -293,10 +332,20 @@ impl Piece {
         &self.name
     }
 
+    #[allow(dead_code)]
     pub fn get_color(&self) -> Color32 {
         self.color
     }
 
+    pub fn get_rotation(&self) -> u8 {
+        self.rotation
+    }
+
+    // Top-left corner of the piece's shape grid as (row, col)
+    pub fn get_position(&self) -> (i32, i32) {
+        (self.ypos, self.xpos)
+    }
+
     pub fn move_left(&mut self) {
         self.xpos -= 1;
     }
@@ -317,10 +366,41 @@ impl Piece {
         self.rotation = (self.rotation + 1) % 4;
     }
 
-    pub fn rotate_counterclockwise(&mut self) {
-        self.rotation = if self.rotation == 0 { 3 } else { self.rotation - 1 };
+    // Rotate using the SRS kick table, returning the index of the kick that succeeded.
+    // The piece is left untouched if every kick collides.
+    pub fn rotate(&mut self, clockwise: bool, board: &Board) -> Option<usize> {
+        let from = self.rotation;
+        let to = if clockwise { (from + 1) % 4 } else { (from + 3) % 4 };
+
+        let kicks = match self.shape {
+            PieceShape::Large(_) => &I_KICKS[kick_index(from, to)],
+            PieceShape::Small(_) => &JLSTZ_KICKS[kick_index(from, to)],
+            PieceShape::Mini(_) => {
+                // O piece never needs to kick
+                self.rotation = to;
+                return Some(0);
+            }
+        };
+
+        let (start_x, start_y) = (self.xpos, self.ypos);
+        self.rotation = to;
+        for (i, (dx, dy)) in kicks.iter().enumerate() {
+            // Kick table y points up, board rows grow downwards
+            self.xpos = start_x + dx;
+            self.ypos = start_y - dy;
+            if board.is_valid_position(&self.get_blocks()) {
+                return Some(i);
+            }
+        }
+
+        // No kick fit, restore original state
+        self.rotation = from;
+        self.xpos = start_x;
+        self.ypos = start_y;
+        None
     }
 
+    #[allow(dead_code)]
     pub fn rotate_180(&mut self) {
         self.rotation = (self.rotation + 2) % 4;
     }
@@ -371,4 +451,42 @@ impl Piece {
         let [r, g, b, _] = self.color.to_array();
         Color32::from_rgba_unmultiplied(r, g, b, alpha)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_rotating_against_the_left_wall_kicks_right() {
+        let board = Board::new();
+        let mut piece = Piece::create_piece("T");
+        assert_eq!(piece.rotate(true, &board), Some(0));
+        for _ in 0..4 {
+            piece.move_left();
+        }
+        assert_eq!(piece.get_position(), (0, -1));
+
+        // Pointing down the T no longer fits, so the 1->2 table's second test (+1, 0) applies
+        assert_eq!(piece.rotate(true, &board), Some(1));
+        assert_eq!(piece.get_rotation(), 2);
+        assert_eq!(piece.get_position(), (0, 0));
+    }
+
+    #[test]
+    fn rotation_with_no_free_kick_leaves_the_piece_alone() {
+        // Everything but the I's own column is filled
+        let mut ids = vec![1; Board::get_width() * Board::get_height()];
+        for row in 0..Board::get_height() {
+            ids[row * Board::get_width() + 5] = 0;
+        }
+        let board = Board::from_ids(&ids);
+        let mut piece = Piece::create_piece("I");
+        piece.rotate_clockwise();
+        assert!(board.is_valid_position(&piece.get_blocks()));
+
+        assert_eq!(piece.rotate(true, &board), None);
+        assert_eq!(piece.get_rotation(), 1);
+        assert_eq!(piece.get_position(), (0, 3));
+    }
+}
@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use super::randomizer::RandomizerKind;
+use crate::enums::game_modes::GameMode;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,9 +14,37 @@ pub struct GameStats {
     pub triples: u32,
     pub quadruples: u32,
     pub max_combo: u32,
+    // Clears that got the back-to-back bonus
     pub back_to_backs: u32,
+    // Most back-to-back bonuses in a row
+    pub longest_back_to_back: u32,
+    // Clears that left the board empty
+    pub perfect_clears: u32,
+    pub t_spin_zeros: u32,
+    pub t_spin_singles: u32,
+    pub t_spin_doubles: u32,
+    pub t_spin_triples: u32,
+    pub t_spin_minis: u32,
+    // Whole seconds of play, kept for older games that have no play_time_ms
     pub duration_seconds: u64,
+    // In-game time in milliseconds, counted by the engine so pauses are left out
+    pub play_time_ms: u64,
     pub timestamp: u64,
+    pub seed: u64,
+    pub randomizer: RandomizerKind,
+    pub mode: GameMode,
+    // Time to reach the mode's goal, only set when the game was completed
+    pub finish_time_ms: Option<u64>,
+    // Pieces placed with more inputs than needed
+    pub finesse_faults: u32,
+    pub key_presses: u32,
+    // Garbage lines sent by clears, before any cancelling
+    pub attack_sent: u32,
+    // Rates over in-game time, which leaves out pauses
+    pub pps: f32,
+    pub kpp: f32,
+    pub lines_per_minute: f32,
+    pub attack_per_minute: f32,
 }
 
 impl GameStats {
@@ -30,13 +60,36 @@ impl GameStats {
             quadruples: 0,
             max_combo: 0,
             back_to_backs: 0,
+            longest_back_to_back: 0,
+            perfect_clears: 0,
+            t_spin_zeros: 0,
+            t_spin_singles: 0,
+            t_spin_doubles: 0,
+            t_spin_triples: 0,
+            t_spin_minis: 0,
             duration_seconds: 0,
+            play_time_ms: 0,
             timestamp: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .unwrap()
                 .as_secs(),
+            seed: 0,
+            randomizer: RandomizerKind::SevenBag,
+            mode: GameMode::Marathon,
+            finish_time_ms: None,
+            finesse_faults: 0,
+            key_presses: 0,
+            attack_sent: 0,
+            pps: 0.0,
+            kpp: 0.0,
+            lines_per_minute: 0.0,
+            attack_per_minute: 0.0,
         }
     }
+
+    pub fn total_t_spins(&self) -> u32 {
+        self.t_spin_zeros + self.t_spin_singles + self.t_spin_doubles + self.t_spin_triples + self.t_spin_minis
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,10 +103,15 @@ pub struct PlayerStats {
     pub total_triples: u64,
     pub total_quadruples: u64,
     pub total_back_to_backs: u64,
+    pub total_t_spins: u64,
     pub highest_score: u32,
     pub highest_level: u32,
     pub longest_combo: u32,
     pub total_playtime_seconds: u64,
+    #[serde(default)]
+    pub versus_wins: u32,
+    #[serde(default)]
+    pub versus_losses: u32,
     pub game_history: Vec<GameStats>,
 }
 
@@ -69,10 +127,13 @@ impl PlayerStats {
             total_triples: 0,
             total_quadruples: 0,
             total_back_to_backs: 0,
+            total_t_spins: 0,
             highest_score: 0,
             highest_level: 0,
             longest_combo: 0,
             total_playtime_seconds: 0,
+            versus_wins: 0,
+            versus_losses: 0,
             game_history: Vec::new(),
         }
     }
@@ -87,6 +148,7 @@ impl PlayerStats {
         self.total_triples += game_stats.triples as u64;
         self.total_quadruples += game_stats.quadruples as u64;
         self.total_back_to_backs += game_stats.back_to_backs as u64;
+        self.total_t_spins += game_stats.total_t_spins() as u64;
         self.total_playtime_seconds += game_stats.duration_seconds;
 
         // Update personal bests
@@ -115,6 +177,7 @@ impl PlayerStats {
         }
     }
 
+    #[allow(dead_code)]
     pub fn get_average_lines(&self) -> f64 {
         if self.total_games == 0 {
             0.0
@@ -123,6 +186,7 @@ impl PlayerStats {
         }
     }
 
+    #[allow(dead_code)]
     pub fn get_recent_games(&self, count: usize) -> &[GameStats] {
         let start = self.game_history.len().saturating_sub(count);
         &self.game_history[start..]
@@ -151,10 +215,12 @@ impl Player {
     }
 
     // Serialization methods for future SQLite/file storage
+    #[allow(dead_code)]
     pub fn to_json(&self) -> String {
         serde_json::to_string_pretty(&self.stats).unwrap_or_default()
     }
 
+    #[allow(dead_code)]
     pub fn from_json(name: String, json: &str) -> Self {
         let stats = serde_json::from_str(json).unwrap_or_else(|_| PlayerStats::new());
         Self { id: None, name, stats }
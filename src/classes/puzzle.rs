@@ -0,0 +1,39 @@
+use super::board::Board;
+use super::randomizer::PIECE_NAMES;
+use serde::{Deserialize, Serialize};
+use std::io;
+
+// A starting position made in the board editor: the cells, the hold piece and
+// the pieces dealt first, after which the queue carries on at random
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Puzzle {
+    pub name: String,
+    // Board cell ids row by row
+    pub cells: Vec<u8>,
+    pub hold: Option<String>,
+    pub queue: Vec<String>,
+}
+
+impl Puzzle {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    // None unless the JSON is a puzzle with a full board and real piece names
+    pub fn from_json(json: &str) -> Option<Self> {
+        let puzzle: Self = serde_json::from_str(json).ok()?;
+        let board_size = Board::get_width() * Board::get_height();
+        let known = |name: &String| PIECE_NAMES.contains(&name.as_str());
+        (puzzle.cells.len() == board_size && puzzle.hold.iter().all(known) && puzzle.queue.iter().all(known))
+            .then_some(puzzle)
+    }
+
+    pub fn save_to_file(&self, path: &str) -> Result<(), io::Error> {
+        std::fs::write(path, self.to_json())
+    }
+
+    pub fn load_from_file(path: &str) -> Result<Self, io::Error> {
+        let json = std::fs::read_to_string(path)?;
+        Self::from_json(&json).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Not a valid puzzle file"))
+    }
+}
@@ -1,83 +1,127 @@
 use super::piece::Piece;
+use super::randomizer::{Randomizer, RandomizerKind, PIECE_NAMES};
 use eframe::egui;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+// Pieces generated ahead of time by default, enough to fill the preview
+const QUEUE_LENGTH: usize = 7;
+
+// Mixed into the seed for the order bags started by with_bags are finished in,
+// so the randomizer's own sequence is left alone
+const BAG_SEED_SALT: u64 = 0xD1B5_4A32_D192_ED03;
 
 pub struct Queue {
     pieces: Vec<Piece>,
-    next_pieces: Vec<Piece>,
-    bag: Vec<&'static str>,
+    // Pieces kept ready ahead of the current one
+    length: usize,
+    // Pieces dealt in this order before the randomizer takes over
+    preset: Vec<String>,
+    randomizer: Box<dyn Randomizer>,
+    randomizer_kind: RandomizerKind,
+    seed: u64,
+    rng: StdRng,
 }
 
 impl Queue {
-    pub fn new() -> Self {
+    // The same seed always deals the same piece sequence
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_randomizer(RandomizerKind::SevenBag, seed)
+    }
+
+    pub fn with_randomizer(randomizer_kind: RandomizerKind, seed: u64) -> Self {
         Self {
             pieces: Vec::new(),
-            next_pieces: Vec::new(),
-            bag: vec!["I", "O", "T", "S", "Z", "J", "L"],
+            length: QUEUE_LENGTH,
+            preset: Vec::new(),
+            randomizer: randomizer_kind.create(),
+            randomizer_kind,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
         }
     }
 
-    pub fn generate_seven_bag(&mut self) {
-        use rand::seq::SliceRandom;
-        let mut rng = rand::thread_rng();
-        let mut shuffled_bag = self.bag.clone();
-        shuffled_bag.shuffle(&mut rng);
-        
-        if self.pieces.is_empty() {
-            for name in shuffled_bag.iter() {
-                self.pieces.push(Piece::create_piece(name));
-            }
-            shuffled_bag.shuffle(&mut rng);
-            for name in shuffled_bag.iter() {
-                self.next_pieces.push(Piece::create_piece(name));
+    // Deal `names` first, in order, then carry on with the randomizer
+    pub fn with_preset(mut self, names: Vec<String>) -> Self {
+        self.pieces = names.iter().map(|name| Piece::create_piece(name)).collect();
+        self.preset = names;
+        self
+    }
+
+    // Deal `names` first like with_preset, finishing every 7-bag they start
+    // with its other pieces in a seeded order. The randomizer's bags line up
+    // after them, so a 7-bag queue never deals a piece twice in a bag.
+    pub fn with_bags(self, names: &[&str]) -> Self {
+        let mut bags: Vec<Vec<&str>> = Vec::new();
+        for name in names.iter().copied() {
+            match bags.last_mut() {
+                Some(bag) if !bag.contains(&name) => bag.push(name),
+                _ => bags.push(vec![name]),
             }
         }
+
+        let mut rng = StdRng::seed_from_u64(self.seed ^ BAG_SEED_SALT);
+        let mut pieces = Vec::new();
+        for mut bag in bags {
+            let mut rest: Vec<&str> = PIECE_NAMES.iter().copied().filter(|piece| !bag.contains(piece)).collect();
+            rest.shuffle(&mut rng);
+            bag.extend(rest);
+            pieces.extend(bag.into_iter().map(str::to_string));
+        }
+        self.with_preset(pieces)
     }
-    
-    pub fn get_piece_names(&self) -> (Vec<String>, Vec<String>) {
-        let current = self.pieces.iter().map(|p| p.get_name().to_string()).collect();
-        let next = self.next_pieces.iter().map(|p| p.get_name().to_string()).collect();
-        (current, next)
+
+    pub fn get_preset(&self) -> &[String] {
+        &self.preset
     }
 
-    pub fn get_next_piece(&mut self) -> Option<Piece> {
-        // If next_pieces is empty, refill it
-        if self.next_pieces.is_empty() {
-            self.generate_new_next_bag();
-        }
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
 
-        // Remove and return the first piece
-        if !self.pieces.is_empty() {
-            let piece = self.pieces.remove(0);
-            // Add the first piece from next_pieces to pieces
-            if !self.next_pieces.is_empty() {
-                let next_piece = self.next_pieces.remove(0);
-                self.pieces.push(next_piece);
-            }
-            Some(piece)
-        } else {
-            self.generate_seven_bag();
-            self.get_next_piece()
-        }
+    pub fn get_randomizer_kind(&self) -> RandomizerKind {
+        self.randomizer_kind
     }
 
-    fn generate_new_next_bag(&mut self) {
-        use rand::seq::SliceRandom;
-        let mut rng = rand::thread_rng();
-        let mut shuffled_bag = self.bag.clone();
-        shuffled_bag.shuffle(&mut rng);
-        
-        for name in shuffled_bag.iter() {
-            self.next_pieces.push(Piece::create_piece(name));
+    // Deal further ahead for modes that preview more pieces
+    pub fn set_length(&mut self, length: usize) {
+        self.length = length;
+    }
+
+    // Top the queue up so the preview is always full
+    pub fn fill(&mut self) {
+        while self.pieces.len() < self.length {
+            let name = self.randomizer.next_piece(&mut self.rng);
+            self.pieces.push(Piece::create_piece(name));
         }
     }
     
+    pub fn get_piece_names(&self) -> Vec<String> {
+        self.pieces.iter().map(|p| p.get_name().to_string()).collect()
+    }
+
+    // The piece that comes out next, without taking it
+    pub fn peek(&self) -> Option<&Piece> {
+        self.pieces.first()
+    }
+
+    pub fn get_next_piece(&mut self) -> Option<Piece> {
+        self.fill();
+
+        // Remove and return the first piece
+        let piece = self.pieces.remove(0);
+        self.fill();
+        Some(piece)
+    }
+    
     pub fn draw(&self, ui: &mut egui::Ui, num_pieces: usize) {
         ui.vertical(|ui| {
             ui.heading("Next Pieces");
             ui.add_space(10.0);
             
             // Draw the first `num_pieces` from the queue
-            for (_i, piece) in self.pieces.iter().take(num_pieces).enumerate() {
+            for piece in self.pieces.iter().take(num_pieces) {
                 // Draw the piece preview
                 piece.draw_preview(ui, 15.0);
                 ui.add_space(5.0);
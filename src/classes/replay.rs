@@ -0,0 +1,205 @@
+use serde::{Deserialize, Serialize};
+use super::engine::{Engine, Handling, FRAME_TIME};
+use super::opener::Opener;
+use super::queue::Queue;
+use super::randomizer::RandomizerKind;
+use crate::enums::game_actions::GameAction;
+use crate::enums::game_modes::GameMode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ReplayEvent {
+    Press(GameAction),
+    Release(GameAction),
+    SetHandling(Handling),
+    // Versus garbage received from the opponent
+    Garbage(u32),
+}
+
+// An event applied right before the engine ran `frame`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ReplayInput {
+    pub frame: u64,
+    pub event: ReplayEvent,
+}
+
+// Everything needed to re-simulate a game: the queue it was dealt from, the
+// handling it started with and every input, stamped by engine frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub randomizer: RandomizerKind,
+    pub handling: Handling,
+    #[serde(default)]
+    pub mode: GameMode,
+    #[serde(default)]
+    pub line_cap: Option<u32>,
+    // Cells the board started with, None for an empty board
+    #[serde(default)]
+    pub starting_board: Option<Vec<u8>>,
+    #[serde(default)]
+    pub starting_hold: Option<String>,
+    // Pieces dealt before the randomizer, for puzzles with a fixed queue
+    #[serde(default)]
+    pub preset_queue: Vec<String>,
+    // Opener the placements were graded against
+    #[serde(default)]
+    pub opener: Option<Opener>,
+    pub inputs: Vec<ReplayInput>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, randomizer: RandomizerKind, handling: Handling, mode: GameMode) -> Self {
+        Self {
+            seed,
+            randomizer,
+            handling,
+            mode,
+            line_cap: None,
+            starting_board: None,
+            starting_hold: None,
+            preset_queue: Vec::new(),
+            opener: None,
+            inputs: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, frame: u64, event: ReplayEvent) {
+        self.inputs.push(ReplayInput { frame, event });
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    pub fn from_json(json: &str) -> Option<Self> {
+        serde_json::from_str(json).ok()
+    }
+
+    // Fresh engine in the same starting state as the recorded game
+    pub fn create_engine(&self) -> Engine {
+        let queue = Queue::with_randomizer(self.randomizer, self.seed).with_preset(self.preset_queue.clone());
+        Engine::with_mode(self.handling, queue, self.mode)
+            .with_line_cap(self.line_cap)
+            .with_starting_board(self.starting_board.clone())
+            .with_starting_hold(self.starting_hold.clone())
+            .with_opener(self.opener)
+    }
+}
+
+// Plays a replay back by feeding its inputs to a fresh engine on the frames they were recorded
+pub struct ReplayPlayer {
+    replay: Replay,
+    engine: Engine,
+    next_input: usize,
+    frame_accumulator: f32,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        let engine = replay.create_engine();
+        Self {
+            replay,
+            engine,
+            next_input: 0,
+            frame_accumulator: 0.0,
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.engine = self.replay.create_engine();
+        self.next_input = 0;
+        self.frame_accumulator = 0.0;
+    }
+
+    pub fn engine(&mut self) -> &mut Engine {
+        &mut self.engine
+    }
+
+    pub fn get_frame(&self) -> u64 {
+        self.engine.get_frame()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.engine.is_game_over()
+    }
+
+    // Advance by real elapsed time scaled by the playback speed
+    pub fn step(&mut self, delta_time: f32, speed: f32) {
+        self.frame_accumulator += delta_time * speed;
+        while self.frame_accumulator >= FRAME_TIME && !self.is_finished() {
+            self.frame_accumulator -= FRAME_TIME;
+            self.tick();
+        }
+    }
+
+    // Apply this frame's inputs, then advance exactly one frame
+    pub fn tick(&mut self) {
+        let frame = self.engine.get_frame();
+        while let Some(input) = self.replay.inputs.get(self.next_input) {
+            if input.frame > frame {
+                break;
+            }
+            match input.event {
+                ReplayEvent::Press(action) => self.engine.press(action),
+                ReplayEvent::Release(action) => self.engine.release(action),
+                ReplayEvent::SetHandling(handling) => self.engine.set_handling(handling),
+                ReplayEvent::Garbage(lines) => self.engine.receive_garbage(lines),
+            }
+            self.next_input += 1;
+        }
+        self.engine.tick();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Play a fixed input script: each piece shifts, turns and drops, with some
+    // frames of gravity and DAS in between
+    fn play_script(engine: &mut Engine) {
+        for piece in 0..12u64 {
+            let shift = if piece % 2 == 0 { GameAction::MoveLeft } else { GameAction::MoveRight };
+            engine.press(shift);
+            for _ in 0..piece * 3 {
+                engine.tick();
+            }
+            engine.release(shift);
+            if piece % 3 == 0 {
+                engine.press(GameAction::RotateCW);
+            }
+            if piece % 4 == 1 {
+                engine.press(GameAction::HoldPiece);
+            }
+            for _ in 0..20 {
+                engine.tick();
+            }
+            engine.press(GameAction::HardDrop);
+            engine.tick();
+        }
+    }
+
+    #[test]
+    fn replaying_recorded_inputs_reproduces_the_game() {
+        let mut engine = Engine::with_seed(Handling::default(), 7);
+        play_script(&mut engine);
+        assert!(!engine.is_game_over());
+        assert!(engine.get_pieces_placed() > 0);
+
+        let replay = Replay::from_json(&engine.get_replay().to_json()).unwrap();
+        let mut player = ReplayPlayer::new(replay);
+        while player.get_frame() < engine.get_frame() {
+            player.tick();
+        }
+
+        let replayed = player.engine();
+        assert_eq!(replayed.board.to_ids(), engine.board.to_ids());
+        assert_eq!(replayed.get_score(), engine.get_score());
+        assert_eq!(replayed.get_lines_cleared(), engine.get_lines_cleared());
+        assert_eq!(replayed.get_pieces_placed(), engine.get_pieces_placed());
+        assert_eq!(
+            replayed.current_piece.as_ref().map(|piece| piece.get_blocks()),
+            engine.current_piece.as_ref().map(|piece| piece.get_blocks())
+        );
+    }
+}
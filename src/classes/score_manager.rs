@@ -1,6 +1,62 @@
-const B2B_SCORE: u32 = 1200;
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TSpin {
+    None,
+    Mini,
+    Full,
+}
+
+// What a locked piece cleared, for stats and attack
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClearType {
+    None,
+    Single,
+    Double,
+    Triple,
+    Tetris,
+    TSpinMini(u32),
+    TSpin(u32),
+}
+
+impl ClearType {
+    pub fn new(lines_cleared: u32, t_spin: TSpin) -> Self {
+        match (t_spin, lines_cleared) {
+            (TSpin::Mini, lines) => ClearType::TSpinMini(lines),
+            (TSpin::Full, lines) => ClearType::TSpin(lines),
+            (TSpin::None, 0) => ClearType::None,
+            (TSpin::None, 1) => ClearType::Single,
+            (TSpin::None, 2) => ClearType::Double,
+            (TSpin::None, 3) => ClearType::Triple,
+            (TSpin::None, _) => ClearType::Tetris,
+        }
+    }
+
+    // Tetrises and line clearing T-spins are "difficult" clears
+    pub fn is_difficult(&self) -> bool {
+        match self {
+            ClearType::Tetris => true,
+            ClearType::TSpinMini(lines) | ClearType::TSpin(lines) => *lines > 0,
+            _ => false,
+        }
+    }
+}
+
+// Outcome of scoring one locked piece
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClearResult {
+    pub clear_type: ClearType,
+    // Whether this clear got the back-to-back bonus
+    pub back_to_back: bool,
+    // Back-to-back bonuses in a row including this one, 0 when not active
+    pub back_to_back_chain: u32,
+    pub combo: u32,
+    // The board was left empty
+    pub perfect_clear: bool,
+    pub points: u32,
+}
+
 pub struct ScoreManager {
     pub score: u32,
+    // Difficult clears in a row
     back_to_back: u32,
 }
 
@@ -9,40 +65,149 @@ impl ScoreManager {
         Self { score: 0, back_to_back: 0 }
     }
     
-    pub fn lines_cleared(&mut self, lines_cleared: u32, level:u32, combo: u32,){
-        let base_score = match lines_cleared {
-            1 => 100,
-            2 => 300,
-            3 => 500,
-            4 => 800,
+    pub fn lines_cleared(&mut self, lines_cleared: u32, level:u32, combo: u32, t_spin: TSpin, perfect_clear: bool) -> ClearResult {
+        let clear_type = ClearType::new(lines_cleared, t_spin);
+        let base_score = match clear_type {
+            ClearType::Single => 100,
+            ClearType::Double => 300,
+            ClearType::Triple => 500,
+            ClearType::Tetris => 800,
+            ClearType::TSpinMini(0) => 100,
+            ClearType::TSpinMini(1) => 200,
+            ClearType::TSpinMini(2) => 400,
+            ClearType::TSpin(0) => 400,
+            ClearType::TSpin(1) => 800,
+            ClearType::TSpin(2) => 1200,
+            ClearType::TSpin(3) => 1600,
             _ => 0,
         };
         
         let mut total_score = base_score * level;
+        let difficult = clear_type.is_difficult();
 
-        // Apply back-to-back bonus
-        if self.back_to_back > 0 && lines_cleared == 4 {
-            total_score = B2B_SCORE * level;
+        // Apply back-to-back bonus (x1.5)
+        let back_to_back = self.back_to_back > 0 && difficult;
+        if back_to_back {
+            total_score = total_score * 3 / 2;
         }
         
-        // Update back-to-back counter
-        if lines_cleared == 4 {
+        // Update back-to-back counter, T-spins without lines neither build nor break it
+        if difficult {
             self.back_to_back += 1;
-        } else {
+        } else if lines_cleared > 0 {
             self.back_to_back = 0;
         }
         // Apply combo bonus
         if combo > 1 {
             total_score += combo * 50 * level;
         }
+
+        // Perfect clear bonus on top, a back-to-back Tetris perfect clear is worth the most
+        let perfect_clear = perfect_clear && lines_cleared > 0;
+        if perfect_clear {
+            let bonus = match lines_cleared {
+                1 => 800,
+                2 => 1200,
+                3 => 1800,
+                _ if back_to_back => 3200,
+                _ => 2000,
+            };
+            total_score += bonus * level;
+        }
         self.score += total_score;
+
+        ClearResult {
+            clear_type,
+            back_to_back,
+            back_to_back_chain: self.get_back_to_back_chain(),
+            combo,
+            perfect_clear,
+            points: total_score,
+        }
     }
     
+    // Back-to-back bonuses in a row so far, the first difficult clear only starts the chain
+    pub fn get_back_to_back_chain(&self) -> u32 {
+        self.back_to_back.saturating_sub(1)
+    }
+
     pub fn get_score(&self) -> u32 {
         self.score
     }
 
     pub fn drop(&mut self, drop_type: u32, cells_dropped: u32){
-        self.score += (drop_type * cells_dropped) as u32;
+        self.score += drop_type * cells_dropped;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn back_to_back_chains_until_an_easy_clear() {
+        let mut scores = ScoreManager::new();
+        // (lines, T-spin, points, back-to-back, chain) at level 1
+        let steps = [
+            (4, TSpin::None, 800, false, 0),
+            (4, TSpin::None, 1200, true, 1),
+            (2, TSpin::Full, 1800, true, 2),
+            // A mini that clears a line is difficult too, so the chain goes on
+            (1, TSpin::Mini, 300, true, 3),
+            // T-spins that clear nothing neither build nor break it
+            (0, TSpin::Mini, 100, false, 3),
+            (0, TSpin::Full, 400, false, 3),
+            (1, TSpin::None, 100, false, 0),
+            (4, TSpin::None, 800, false, 0),
+        ];
+        for (i, (lines, t_spin, points, back_to_back, chain)) in steps.into_iter().enumerate() {
+            let result = scores.lines_cleared(lines, 1, 0, t_spin, false);
+            assert_eq!(
+                (result.points, result.back_to_back, result.back_to_back_chain),
+                (points, back_to_back, chain),
+                "step {}",
+                i
+            );
+        }
+        assert_eq!(scores.get_score(), 5500);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn back_to_back_multiplies_the_level_scaled_score() {
+        let mut scores = ScoreManager::new();
+        scores.lines_cleared(4, 2, 0, TSpin::None, false);
+        let result = scores.lines_cleared(4, 2, 0, TSpin::None, false);
+        assert_eq!(result.clear_type, ClearType::Tetris);
+        assert_eq!(result.points, 2400);
+    }
+
+    #[test]
+    fn perfect_clears_add_the_guideline_bonus() {
+        // (lines, Tetris before it, points) at level 1
+        let cases = [
+            (1, false, 100 + 800),
+            (2, false, 300 + 1200),
+            (3, false, 500 + 1800),
+            (4, false, 800 + 2000),
+            // Back-to-back Tetris perfect clear
+            (4, true, 1200 + 3200),
+        ];
+        for (lines, after_tetris, points) in cases {
+            let mut scores = ScoreManager::new();
+            if after_tetris {
+                scores.lines_cleared(4, 1, 0, TSpin::None, false);
+            }
+            let result = scores.lines_cleared(lines, 1, 0, TSpin::None, true);
+            assert!(result.perfect_clear);
+            assert_eq!(result.points, points, "{} lines, after a Tetris: {}", lines, after_tetris);
+        }
+    }
+
+    #[test]
+    fn an_empty_board_without_a_clear_is_no_perfect_clear() {
+        let mut scores = ScoreManager::new();
+        let result = scores.lines_cleared(0, 1, 0, TSpin::None, true);
+        assert!(!result.perfect_clear);
+        assert_eq!(result.points, 0);
+    }
+}
@@ -3,12 +3,25 @@ use super::screens;
 use crate::enums::states::GameState;
 use super::game::Game;
 use super::database::database::DbManager;
+use super::replay::Replay;
+use super::versus::VersusMatch;
+use super::net_versus::NetVersusMatch;
+use super::puzzle::Puzzle;
+use crate::enums::game_modes::GameMode;
+use crate::enums::cpu_difficulty::CpuDifficulty;
 
 pub struct ScreenManager {
     player_name_input: String,
     leaderboard_state: screens::leaderboard::LeaderboardState,
     pub player_creation_error: Option<String>,
     options_screen: screens::options::OptionsScreen,
+    replay_viewer: Option<screens::replay_viewer::ReplayViewer>,
+    selected_mode: GameMode,
+    cpu_difficulty: CpuDifficulty,
+    versus_match: Option<VersusMatch>,
+    network_setup: screens::network_setup::NetworkSetupScreen,
+    net_match: Option<NetVersusMatch>,
+    board_editor: screens::board_editor::BoardEditorScreen,
 }
 
 impl ScreenManager {
@@ -18,9 +31,28 @@ impl ScreenManager {
             leaderboard_state: screens::leaderboard::LeaderboardState::new(),
             player_creation_error: None,
             options_screen: screens::options::OptionsScreen::default(),
+            replay_viewer: None,
+            selected_mode: GameMode::default(),
+            cpu_difficulty: CpuDifficulty::default(),
+            versus_match: None,
+            network_setup: screens::network_setup::NetworkSetupScreen::default(),
+            net_match: None,
+            board_editor: screens::board_editor::BoardEditorScreen::default(),
         }
     }
 
+    pub fn open_replay(&mut self, replay: Replay, from_leaderboard: bool) {
+        self.replay_viewer = Some(screens::replay_viewer::ReplayViewer::new(replay, from_leaderboard));
+    }
+
+    pub fn open_versus(&mut self, versus_match: VersusMatch) {
+        self.versus_match = Some(versus_match);
+    }
+
+    pub fn versus_match(&mut self) -> Option<&mut VersusMatch> {
+        self.versus_match.as_mut()
+    }
+
     pub fn draw(&mut self, game: &mut Game, ui: &mut egui::Ui, player_name: &str, db_manager: &DbManager, options: &mut crate::classes::game_options::GameOptions) -> Option<ScreenAction> {
         match game.get_state() {
             GameState::PlayerCreation => {
@@ -54,14 +86,17 @@ impl ScreenManager {
             },
             GameState::Menu => {
                 let has_active_game = game.is_game_active();
-                if let Some(action) = screens::menu::draw(ui, player_name, has_active_game) {
+                if let Some(action) = screens::menu::draw(ui, player_name, has_active_game, &mut self.selected_mode) {
                     return Some(match action {
-                        screens::menu::MenuAction::StartGame => ScreenAction::StartGame,
+                        screens::menu::MenuAction::StartGame => ScreenAction::StartGame(self.selected_mode),
                         screens::menu::MenuAction::ResumeGame => ScreenAction::ResumeGame,
                         screens::menu::MenuAction::NewPlayer => ScreenAction::ShowPlayerCreation,
                         screens::menu::MenuAction::SelectPlayer => ScreenAction::ShowPlayerSelection,
                         screens::menu::MenuAction::ShowLeaderboard => ScreenAction::ShowLeaderboard,
                         screens::menu::MenuAction::ShowOptions => ScreenAction::ShowOptions,
+                        screens::menu::MenuAction::Versus => ScreenAction::ShowVersusSetup,
+                        screens::menu::MenuAction::NetworkVersus => ScreenAction::ShowNetworkSetup,
+                        screens::menu::MenuAction::BoardEditor => ScreenAction::ShowBoardEditor,
                     });
                 }
                 None
@@ -69,12 +104,13 @@ impl ScreenManager {
             GameState::Leaderboard => {
                 // Fetch leaderboard data for current category
                 let leaderboard_data = db_manager
-                    .get_leaderboard(10, self.leaderboard_state.current_category)
+                    .get_leaderboard(10, self.leaderboard_state.current_category, self.leaderboard_state.randomizer_filter)
                     .unwrap_or_default();
                 
                 if let Some(action) = screens::leaderboard::draw(ui, &mut self.leaderboard_state, &leaderboard_data) {
                     return Some(match action {
                         screens::leaderboard::LeaderboardAction::Back => ScreenAction::BackToMenu,
+                        screens::leaderboard::LeaderboardAction::WatchReplay(game_id) => ScreenAction::WatchReplay(game_id),
                     });
                 }
                 None
@@ -111,8 +147,119 @@ impl ScreenManager {
             },
             GameState::GameOver => {
                 game.draw_game_board(ui);
-                if screens::game_over::draw(ui, game.get_score(), game.get_lines_cleared()) {
-                    return Some(ScreenAction::RestartGame);
+                if let Some(action) = screens::game_over::draw(ui, &game.engine) {
+                    return Some(match action {
+                        screens::game_over::GameOverAction::PlayAgain => ScreenAction::RestartGame,
+                        screens::game_over::GameOverAction::WatchReplay => ScreenAction::WatchLastReplay,
+                    });
+                }
+                None
+            },
+            GameState::VersusSetup => {
+                let opponents = db_manager.get_all_players()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|(_, name)| name != player_name)
+                    .map(|(id, name)| screens::player_selection::PlayerInfo { id, name })
+                    .collect::<Vec<_>>();
+
+                if let Some(action) = screens::versus_setup::draw(ui, player_name, &opponents, &mut self.cpu_difficulty) {
+                    return Some(match action {
+                        screens::versus_setup::VersusSetupAction::StartMatch(player_id) => ScreenAction::StartVersus(player_id),
+                        screens::versus_setup::VersusSetupAction::StartCpuMatch(difficulty) => ScreenAction::StartCpuVersus(difficulty),
+                        screens::versus_setup::VersusSetupAction::Back => ScreenAction::BackToMenu,
+                    });
+                }
+                None
+            },
+            GameState::Versus => {
+                let Some(versus_match) = &mut self.versus_match else {
+                    return Some(ScreenAction::BackToMenu);
+                };
+                let action = screens::versus::draw(ui, versus_match, options.ghost_piece_alpha);
+
+                // Hand the result over once so it gets recorded
+                if let Some(winner) = versus_match.take_result() {
+                    return Some(ScreenAction::VersusFinished(winner));
+                }
+                match action {
+                    Some(screens::versus::VersusAction::Rematch) => versus_match.start(),
+                    Some(screens::versus::VersusAction::BackToMenu) => {
+                        self.versus_match = None;
+                        return Some(ScreenAction::BackToMenu);
+                    },
+                    None => {},
+                }
+                None
+            },
+            GameState::NetworkSetup => {
+                if let Some(action) = self.network_setup.draw(ui) {
+                    return Some(match action {
+                        screens::network_setup::NetworkSetupAction::Connected(connection, is_host) => {
+                            self.net_match = Some(NetVersusMatch::new(options, player_name, connection, is_host));
+                            ScreenAction::ShowNetworkVersus
+                        },
+                        screens::network_setup::NetworkSetupAction::Back => ScreenAction::BackToMenu,
+                    });
+                }
+                None
+            },
+            GameState::NetworkVersus => {
+                let Some(net_match) = &mut self.net_match else {
+                    return Some(ScreenAction::BackToMenu);
+                };
+                match screens::net_versus::draw(ui, net_match, player_name, options.ghost_piece_alpha) {
+                    Some(screens::net_versus::NetVersusAction::Rematch) => net_match.rematch(),
+                    Some(screens::net_versus::NetVersusAction::Leave) => {
+                        // Dropping the match closes the connection
+                        self.net_match = None;
+                        return Some(ScreenAction::BackToMenu);
+                    },
+                    None => {},
+                }
+                None
+            },
+            GameState::BoardEditor => {
+                let puzzles = db_manager.get_puzzles().unwrap_or_default();
+                match self.board_editor.draw(ui, &puzzles) {
+                    Some(screens::board_editor::BoardEditorAction::Play(puzzle)) => {
+                        return Some(ScreenAction::StartPuzzle(puzzle));
+                    },
+                    Some(screens::board_editor::BoardEditorAction::Save(puzzle)) => {
+                        let status = match db_manager.save_puzzle(&puzzle) {
+                            Ok(()) => format!("Saved {}", puzzle.name),
+                            Err(e) => format!("Could not save: {}", e),
+                        };
+                        self.board_editor.set_status(status);
+                    },
+                    Some(screens::board_editor::BoardEditorAction::Delete(name)) => {
+                        let status = match db_manager.delete_puzzle(&name) {
+                            Ok(()) => format!("Deleted {}", name),
+                            Err(e) => format!("Could not delete: {}", e),
+                        };
+                        self.board_editor.set_status(status);
+                    },
+                    Some(screens::board_editor::BoardEditorAction::Back) => return Some(ScreenAction::BackToMenu),
+                    None => {},
+                }
+                None
+            },
+            GameState::Replay => {
+                let Some(viewer) = &mut self.replay_viewer else {
+                    return Some(ScreenAction::BackToMenu);
+                };
+                if let Some(action) = viewer.draw(ui, options.ghost_piece_alpha) {
+                    return Some(match action {
+                        screens::replay_viewer::ReplayViewerAction::Close => {
+                            let from_leaderboard = viewer.from_leaderboard;
+                            self.replay_viewer = None;
+                            if from_leaderboard {
+                                ScreenAction::ShowLeaderboard
+                            } else {
+                                ScreenAction::BackToMenu
+                            }
+                        },
+                    });
                 }
                 None
             },
@@ -121,15 +268,27 @@ impl ScreenManager {
 }
 
 pub enum ScreenAction {
-    StartGame,
+    StartGame(GameMode),
     RestartGame,
     ResumeGame,
     CreatePlayer(String),
+    #[allow(dead_code)]
     CreatePlayerError(String),
     ShowPlayerCreation,
     SelectPlayer(i64),
     ShowPlayerSelection,
     ShowLeaderboard,
     ShowOptions,
+    WatchReplay(i64),
+    WatchLastReplay,
+    ShowVersusSetup,
+    StartVersus(i64),
+    StartCpuVersus(CpuDifficulty),
+    // Index of the winning side, None for a draw
+    VersusFinished(Option<usize>),
+    ShowNetworkSetup,
+    ShowNetworkVersus,
+    ShowBoardEditor,
+    StartPuzzle(Puzzle),
     BackToMenu,
 }
@@ -0,0 +1,220 @@
+use eframe::egui;
+use crate::classes::board::{self, Board, GARBAGE_ID};
+use crate::classes::puzzle::Puzzle;
+use crate::classes::randomizer::PIECE_NAMES;
+
+// Cells are drawn smaller so the tools fit next to the board
+const EDITOR_CELL_SIZE: f32 = 24.0;
+
+pub enum BoardEditorAction {
+    Play(Puzzle),
+    Save(Puzzle),
+    Delete(String),
+    Back,
+}
+
+// Paint a board, pick the hold piece and the first pieces of the queue, then
+// play from there or keep it as a named puzzle
+pub struct BoardEditorScreen {
+    board: Board,
+    // Cell id the primary button paints, the secondary button always erases
+    brush: u8,
+    hold: Option<String>,
+    queue: Vec<String>,
+    name: String,
+    file_path: String,
+    status: Option<String>,
+}
+
+impl Default for BoardEditorScreen {
+    fn default() -> Self {
+        Self {
+            board: editor_board(&Board::new().to_ids()),
+            brush: GARBAGE_ID,
+            hold: None,
+            queue: Vec::new(),
+            name: String::new(),
+            file_path: "puzzle.json".to_string(),
+            status: None,
+        }
+    }
+}
+
+impl BoardEditorScreen {
+    pub fn set_status(&mut self, status: String) {
+        self.status = Some(status);
+    }
+
+    // The position as it stands in the editor
+    fn puzzle(&self) -> Puzzle {
+        Puzzle {
+            name: self.name.trim().to_string(),
+            cells: self.board.to_ids(),
+            hold: self.hold.clone(),
+            queue: self.queue.clone(),
+        }
+    }
+
+    fn load(&mut self, puzzle: Puzzle) {
+        self.board = editor_board(&puzzle.cells);
+        self.hold = puzzle.hold;
+        self.queue = puzzle.queue;
+        self.name = puzzle.name;
+    }
+
+    pub fn draw(&mut self, ui: &mut egui::Ui, puzzles: &[Puzzle]) -> Option<BoardEditorAction> {
+        let mut action = None;
+
+        ui.vertical_centered(|ui| {
+            ui.heading("Board Editor");
+            ui.add_space(10.0);
+        });
+
+        ui.horizontal(|ui| {
+            if let Some((row, col, erase)) = self.board.draw_editable(ui) {
+                self.board.set_cell(row, col, if erase { 0 } else { self.brush });
+            }
+            ui.add_space(20.0);
+
+            ui.vertical(|ui| {
+                ui.label("Brush (right click erases):");
+                ui.horizontal_wrapped(|ui| {
+                    for (i, name) in PIECE_NAMES.iter().enumerate() {
+                        brush_button(ui, &mut self.brush, i as u8 + 1, name);
+                    }
+                    brush_button(ui, &mut self.brush, GARBAGE_ID, "Garbage");
+                    brush_button(ui, &mut self.brush, 0, "Empty");
+                });
+                if ui.button("Clear Board").clicked() {
+                    self.board = editor_board(&Board::new().to_ids());
+                }
+                ui.add_space(15.0);
+
+                ui.horizontal(|ui| {
+                    ui.label("Hold:");
+                    egui::ComboBox::from_id_salt("editor_hold")
+                        .selected_text(self.hold.as_deref().unwrap_or("Empty"))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.hold, None, "Empty");
+                            for name in PIECE_NAMES {
+                                ui.selectable_value(&mut self.hold, Some(name.to_string()), name);
+                            }
+                        });
+                });
+                ui.add_space(15.0);
+
+                // Pieces dealt first, in order, before the randomizer carries on
+                ui.label("Queue:");
+                ui.horizontal(|ui| {
+                    for name in PIECE_NAMES {
+                        if ui.button(name).clicked() {
+                            self.queue.push(name.to_string());
+                        }
+                    }
+                });
+                if self.queue.is_empty() {
+                    ui.label("Random pieces");
+                } else {
+                    ui.label(format!("{} then random", self.queue.join(" ")));
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Remove Last").clicked() {
+                        self.queue.pop();
+                    }
+                    if ui.button("Clear Queue").clicked() {
+                        self.queue.clear();
+                    }
+                });
+                ui.add_space(15.0);
+
+                if ui.button("Play").clicked() {
+                    action = Some(BoardEditorAction::Play(self.puzzle()));
+                }
+                ui.add_space(15.0);
+
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    ui.add(egui::TextEdit::singleline(&mut self.name).desired_width(140.0));
+                    let named = !self.name.trim().is_empty();
+                    if ui.add_enabled(named, egui::Button::new("Save")).clicked() {
+                        action = Some(BoardEditorAction::Save(self.puzzle()));
+                    }
+                });
+                ui.add_space(5.0);
+
+                ui.label("Saved Puzzles:");
+                if puzzles.is_empty() {
+                    ui.label("None yet");
+                }
+                egui::ScrollArea::vertical()
+                    .id_salt("editor_puzzles")
+                    .max_height(120.0)
+                    .show(ui, |ui| {
+                        for puzzle in puzzles {
+                            ui.horizontal(|ui| {
+                                ui.label(&puzzle.name);
+                                if ui.button("Load").clicked() {
+                                    self.load(puzzle.clone());
+                                    self.status = None;
+                                }
+                                if ui.button("Delete").clicked() {
+                                    action = Some(BoardEditorAction::Delete(puzzle.name.clone()));
+                                }
+                            });
+                        }
+                    });
+                ui.add_space(15.0);
+
+                ui.horizontal(|ui| {
+                    ui.label("File:");
+                    ui.add(egui::TextEdit::singleline(&mut self.file_path).desired_width(140.0));
+                });
+                ui.horizontal(|ui| {
+                    let path = self.file_path.trim().to_string();
+                    if ui.button("Export JSON").clicked() {
+                        self.status = Some(match self.puzzle().save_to_file(&path) {
+                            Ok(()) => format!("Exported to {}", path),
+                            Err(e) => format!("Could not export: {}", e),
+                        });
+                    }
+                    if ui.button("Import JSON").clicked() {
+                        self.status = Some(match Puzzle::load_from_file(&path) {
+                            Ok(puzzle) => {
+                                self.load(puzzle);
+                                format!("Imported {}", path)
+                            }
+                            Err(e) => format!("Could not import: {}", e),
+                        });
+                    }
+                });
+
+                if let Some(status) = &self.status {
+                    ui.add_space(10.0);
+                    ui.label(status);
+                }
+                ui.add_space(15.0);
+
+                if ui.button("Back to Menu").clicked() {
+                    action = Some(BoardEditorAction::Back);
+                }
+            });
+        });
+
+        action
+    }
+}
+
+fn editor_board(cells: &[u8]) -> Board {
+    let mut board = Board::from_ids(cells);
+    board.cell_size = EDITOR_CELL_SIZE;
+    board
+}
+
+// Brush picker entry, labelled in the color it paints
+fn brush_button(ui: &mut egui::Ui, brush: &mut u8, id: u8, label: &str) {
+    let text = match board::get_color_from_id(id) {
+        Some(color) => egui::RichText::new(label).color(color),
+        None => egui::RichText::new(label),
+    };
+    ui.selectable_value(brush, id, text);
+}
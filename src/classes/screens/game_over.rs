@@ -1,7 +1,16 @@
 use eframe::egui;
+use crate::classes::engine::Engine;
+use crate::classes::game::{format_time, grade_color};
+use crate::classes::opener::PlacementGrade;
+use crate::enums::game_modes::GameMode;
 
-pub fn draw(ui: &mut egui::Ui, score: u32, lines_cleared: u32) -> bool {
-    let mut should_restart = false;
+pub enum GameOverAction {
+    PlayAgain,
+    WatchReplay,
+}
+
+pub fn draw(ui: &mut egui::Ui, engine: &Engine) -> Option<GameOverAction> {
+    let mut action = None;
     
     // Draw overlay background
     let screen_rect = ui.ctx().screen_rect();
@@ -21,19 +30,61 @@ pub fn draw(ui: &mut egui::Ui, score: u32, lines_cleared: u32) -> bool {
                 .inner_margin(30.0)
                 .show(ui, |ui| {
                     ui.vertical_centered(|ui| {
-                        ui.heading("GAME OVER");
-                        ui.add_space(20.0);
-                        ui.label(format!("Final Score: {}", score));
-                        ui.label(format!("Lines Cleared: {}", lines_cleared));
+                        if engine.is_completed() && engine.get_mode() == GameMode::Ultra {
+                            ui.heading("TIME'S UP");
+                            ui.add_space(20.0);
+                        } else if engine.is_completed() {
+                            ui.heading(format!("{} COMPLETE", engine.get_mode().display_name().to_uppercase()));
+                            ui.add_space(20.0);
+                            ui.label(egui::RichText::new(format!("Time: {}", format_time(engine.get_time_ms()))).size(20.0).strong());
+                            if engine.get_mode() == GameMode::Dig {
+                                ui.label(format!("Pieces Used: {}", engine.get_pieces_placed()));
+                            }
+                            if engine.get_mode() == GameMode::Opener {
+                                draw_grades(ui, engine);
+                            }
+                        } else if engine.get_mode() == GameMode::PerfectClear {
+                            ui.heading("NO PERFECT CLEAR");
+                            ui.add_space(20.0);
+                        } else if engine.get_mode() == GameMode::Opener {
+                            ui.heading("MISSED PLACEMENT");
+                            ui.add_space(20.0);
+                            draw_grades(ui, engine);
+                        } else {
+                            ui.heading("GAME OVER");
+                            ui.add_space(20.0);
+                            if engine.get_mode() == GameMode::Survival {
+                                ui.label(egui::RichText::new(format!("Survived: {}", format_time(engine.get_time_ms()))).size(20.0).strong());
+                            }
+                        }
+                        ui.label(format!("Final Score: {}", engine.get_score()));
+                        ui.label(format!("Lines Cleared: {}", engine.get_lines_cleared()));
+                        ui.label(format!("Finesse Faults: {}", engine.get_finesse_faults()));
+                        ui.label(format!("PPS: {:.2}  KPP: {:.2}  APM: {:.1}", engine.get_pps(), engine.get_kpp(), engine.get_attack_per_minute()));
                         ui.add_space(20.0);
                         
                         if ui.button("Play Again").clicked() {
-                            should_restart = true;
+                            action = Some(GameOverAction::PlayAgain);
+                        }
+                        ui.add_space(10.0);
+                        if ui.button("Watch Replay").clicked() {
+                            action = Some(GameOverAction::WatchReplay);
                         }
                     });
                 });
         });
     
-    should_restart
+    action
 }
 
+// Grade of every opener placement in order, then how many were perfect
+fn draw_grades(ui: &mut egui::Ui, engine: &Engine) {
+    let grades = engine.get_opener_grades();
+    ui.horizontal(|ui| {
+        for grade in grades {
+            ui.colored_label(grade_color(*grade), grade.display_name());
+        }
+    });
+    let perfect = grades.iter().filter(|grade| **grade == PlacementGrade::Perfect).count();
+    ui.label(format!("Perfect Placements: {}/{}", perfect, grades.len()));
+}
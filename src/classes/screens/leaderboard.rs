@@ -1,26 +1,31 @@
 use eframe::egui;
-use crate::classes::database::database::LeaderboardCategory;
+use crate::classes::database::database::{LeaderboardCategory, LeaderboardEntry};
+use crate::classes::game::format_time;
+use crate::classes::randomizer::RandomizerKind;
 
 pub struct LeaderboardState {
     pub current_category: LeaderboardCategory,
+    pub randomizer_filter: Option<RandomizerKind>,
 }
 
 impl LeaderboardState {
     pub fn new() -> Self {
         Self {
             current_category: LeaderboardCategory::HighScore,
+            randomizer_filter: None,
         }
     }
 }
 
 pub enum LeaderboardAction {
     Back,
+    WatchReplay(i64),
 }
 
 pub fn draw(
     ui: &mut egui::Ui,
     state: &mut LeaderboardState,
-    leaderboard_data: &[(String, u32)],
+    leaderboard_data: &[LeaderboardEntry],
 ) -> Option<LeaderboardAction> {
     let mut action = None;
 
@@ -74,6 +79,64 @@ pub fn draw(
             ).clicked() {
                 state.current_category = LeaderboardCategory::MostBackToBacks;
             }
+
+            if ui.selectable_label(
+                matches!(state.current_category, LeaderboardCategory::LongestBackToBack),
+                "B2B Chain"
+            ).clicked() {
+                state.current_category = LeaderboardCategory::LongestBackToBack;
+            }
+
+            if ui.selectable_label(
+                matches!(state.current_category, LeaderboardCategory::MostPerfectClears),
+                "Perfect Clears"
+            ).clicked() {
+                state.current_category = LeaderboardCategory::MostPerfectClears;
+            }
+
+            if ui.selectable_label(
+                matches!(state.current_category, LeaderboardCategory::SprintTime),
+                "40L Sprint"
+            ).clicked() {
+                state.current_category = LeaderboardCategory::SprintTime;
+            }
+
+            if ui.selectable_label(
+                matches!(state.current_category, LeaderboardCategory::UltraScore),
+                "Ultra"
+            ).clicked() {
+                state.current_category = LeaderboardCategory::UltraScore;
+            }
+
+            if ui.selectable_label(
+                matches!(state.current_category, LeaderboardCategory::DigTime),
+                "Dig"
+            ).clicked() {
+                state.current_category = LeaderboardCategory::DigTime;
+            }
+
+            if ui.selectable_label(
+                matches!(state.current_category, LeaderboardCategory::SurvivalTime),
+                "Survival"
+            ).clicked() {
+                state.current_category = LeaderboardCategory::SurvivalTime;
+            }
+        });
+
+        ui.add_space(10.0);
+
+        // Only compare games dealt by the same randomizer
+        ui.horizontal(|ui| {
+            ui.add_space(20.0);
+            ui.label("Randomizer:");
+            egui::ComboBox::from_id_salt("leaderboard_randomizer")
+                .selected_text(state.randomizer_filter.map_or("All", |kind| kind.display_name()))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut state.randomizer_filter, None, "All");
+                    for kind in RandomizerKind::ALL {
+                        ui.selectable_value(&mut state.randomizer_filter, Some(kind), kind.display_name());
+                    }
+                });
         });
 
         ui.add_space(20.0);
@@ -86,6 +149,12 @@ pub fn draw(
             LeaderboardCategory::MaxCombo => "Maximum Combos",
             LeaderboardCategory::Mostquadruples => "Most quadruples",
             LeaderboardCategory::MostBackToBacks => "Most Back-to-Backs",
+            LeaderboardCategory::LongestBackToBack => "Longest Back-to-Back Chains",
+            LeaderboardCategory::MostPerfectClears => "Most Perfect Clears",
+            LeaderboardCategory::SprintTime => "Fastest 40 Lines",
+            LeaderboardCategory::UltraScore => "2 Minute Ultra Scores",
+            LeaderboardCategory::DigTime => "Fastest Dig Races",
+            LeaderboardCategory::SurvivalTime => "Longest Survival",
         };
         ui.label(egui::RichText::new(category_name).size(18.0).strong());
         ui.add_space(15.0);
@@ -97,7 +166,7 @@ pub fn draw(
             egui::ScrollArea::vertical()
                 .max_height(350.0)
                 .show(ui, |ui| {
-                    for (rank, (name, value)) in leaderboard_data.iter().enumerate() {
+                    for (rank, entry) in leaderboard_data.iter().enumerate() {
                         ui.horizontal(|ui| {
                             // Rank with medal icons for top 3
                             let rank_text = match rank {
@@ -116,11 +185,21 @@ pub fn draw(
                             ui.add_space(10.0);
                             
                             // Player name
-                            ui.label(egui::RichText::new(name).size(16.0));
+                            ui.label(egui::RichText::new(&entry.player_name).size(16.0));
                             
                             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                // Replay of the game that set this entry
+                                if entry.has_replay && ui.small_button("▶ Replay").clicked() {
+                                    action = Some(LeaderboardAction::WatchReplay(entry.game_id));
+                                }
+
                                 // Value
-                                ui.label(egui::RichText::new(format!("{}", value)).size(16.0).strong());
+                                let value = if state.current_category.is_time() {
+                                    format_time(entry.value as u64)
+                                } else {
+                                    entry.value.to_string()
+                                };
+                                ui.label(egui::RichText::new(value).size(16.0).strong());
                             });
                         });
                         
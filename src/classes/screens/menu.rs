@@ -1,4 +1,5 @@
 use eframe::egui;
+use crate::enums::game_modes::GameMode;
 
 pub enum MenuAction {
     StartGame,
@@ -7,9 +8,12 @@ pub enum MenuAction {
     SelectPlayer,
     ShowLeaderboard,
     ShowOptions,
+    Versus,
+    NetworkVersus,
+    BoardEditor,
 }
 
-pub fn draw(ui: &mut egui::Ui, player_name: &str, has_active_game: bool) -> Option<MenuAction> {
+pub fn draw(ui: &mut egui::Ui, player_name: &str, has_active_game: bool, selected_mode: &mut GameMode) -> Option<MenuAction> {
     let mut action = None;
     
     ui.vertical_centered(|ui| {
@@ -28,12 +32,40 @@ pub fn draw(ui: &mut egui::Ui, player_name: &str, has_active_game: bool) -> Opti
             ui.add_space(10.0);
         }
         
+        egui::ComboBox::from_id_salt("menu_game_mode")
+            .selected_text(selected_mode.display_name())
+            .show_ui(ui, |ui| {
+                for mode in GameMode::ALL {
+                    ui.selectable_value(selected_mode, mode, mode.display_name());
+                }
+            });
+
+        ui.add_space(10.0);
+
         if ui.button("Start Game").clicked() {
             action = Some(MenuAction::StartGame);
         }
         
         ui.add_space(10.0);
         
+        if ui.button("Local Versus").clicked() {
+            action = Some(MenuAction::Versus);
+        }
+
+        ui.add_space(10.0);
+
+        if ui.button("LAN Versus").clicked() {
+            action = Some(MenuAction::NetworkVersus);
+        }
+
+        ui.add_space(10.0);
+
+        if ui.button("Board Editor").clicked() {
+            action = Some(MenuAction::BoardEditor);
+        }
+
+        ui.add_space(10.0);
+
         if ui.button("Leaderboard").clicked() {
             action = Some(MenuAction::ShowLeaderboard);
         }
@@ -4,4 +4,10 @@ pub mod game_over;
 pub mod player_creation;
 pub mod player_selection;
 pub mod leaderboard;
-pub mod options;
\ No newline at end of file
+pub mod options;
+pub mod replay_viewer;
+pub mod versus_setup;
+pub mod versus;
+pub mod network_setup;
+pub mod net_versus;
+pub mod board_editor;
\ No newline at end of file
@@ -0,0 +1,75 @@
+use eframe::egui;
+use crate::classes::board::Board;
+use crate::classes::game::draw_engine;
+use crate::classes::net_versus::{NetStatus, NetVersusMatch};
+
+// Same size as the local board in a network match
+const OPPONENT_CELL_SIZE: f32 = 20.0;
+
+pub enum NetVersusAction {
+    Rematch,
+    Leave,
+}
+
+pub fn draw(ui: &mut egui::Ui, net_match: &mut NetVersusMatch, player_name: &str, ghost_alpha: u8) -> Option<NetVersusAction> {
+    let mut action = None;
+
+    net_match.update(ui.ctx());
+    ui.ctx().request_repaint();
+
+    let opponent_name = net_match.opponent_name.clone().unwrap_or_else(|| net_match.peer_address());
+
+    ui.horizontal_top(|ui| {
+        // Our own game
+        ui.vertical(|ui| {
+            ui.label(egui::RichText::new(player_name).size(18.0).strong());
+            ui.add_space(5.0);
+            draw_engine(&mut net_match.game.engine, ui, ghost_alpha);
+        });
+        ui.add_space(20.0);
+
+        // The opponent's last snapshot
+        ui.vertical(|ui| {
+            ui.label(egui::RichText::new(&opponent_name).size(18.0).strong());
+            ui.add_space(5.0);
+            if let Some(snapshot) = &net_match.opponent {
+                let mut board = Board::from_ids(&snapshot.cells);
+                board.cell_size = OPPONENT_CELL_SIZE;
+                board.draw(ui);
+                ui.label(format!("Score: {}", snapshot.score));
+                ui.label(format!("Lines: {}", snapshot.lines_cleared));
+                ui.label(format!("Incoming: {}", snapshot.incoming_garbage));
+            } else {
+                ui.label("Waiting for board...");
+            }
+        });
+    });
+
+    ui.add_space(10.0);
+    ui.vertical_centered(|ui| {
+        match net_match.get_status() {
+            NetStatus::Handshaking => { ui.label("Connecting..."); },
+            NetStatus::WaitingForStart => { ui.label("Waiting for the host to start..."); },
+            NetStatus::Playing => {},
+            NetStatus::Won => { ui.heading("You win!"); },
+            NetStatus::Lost => { ui.heading(format!("{} wins!", opponent_name)); },
+            NetStatus::Draw => { ui.heading("Draw!"); },
+            NetStatus::Disconnected(reason) => {
+                ui.colored_label(egui::Color32::RED, format!("Disconnected: {}", reason));
+            },
+        }
+
+        if net_match.can_rematch() && ui.button("Rematch").clicked() {
+            action = Some(NetVersusAction::Rematch);
+        }
+        if !net_match.is_host() && matches!(net_match.get_status(), NetStatus::Won | NetStatus::Lost | NetStatus::Draw) {
+            ui.label("Waiting for the host to start a rematch...");
+        }
+        ui.add_space(10.0);
+        if ui.button("Leave").clicked() {
+            action = Some(NetVersusAction::Leave);
+        }
+    });
+
+    action
+}
@@ -0,0 +1,103 @@
+use eframe::egui;
+use crate::classes::network::{self, Connection, PendingConnection, DEFAULT_PORT};
+
+pub enum NetworkSetupAction {
+    // Connected to the other player, true when this side is hosting
+    Connected(Connection, bool),
+    Back,
+}
+
+pub struct NetworkSetupScreen {
+    port: String,
+    address: String,
+    pending: Option<PendingConnection>,
+    error: Option<String>,
+}
+
+impl Default for NetworkSetupScreen {
+    fn default() -> Self {
+        Self {
+            port: DEFAULT_PORT.to_string(),
+            address: format!("127.0.0.1:{}", DEFAULT_PORT),
+            pending: None,
+            error: None,
+        }
+    }
+}
+
+impl NetworkSetupScreen {
+    pub fn draw(&mut self, ui: &mut egui::Ui) -> Option<NetworkSetupAction> {
+        let mut action = None;
+
+        // Check on a host waiting for a player or a join in progress
+        if let Some(pending) = &self.pending {
+            if let Some(result) = pending.poll() {
+                let is_host = pending.is_host();
+                self.pending = None;
+                match result {
+                    Ok(connection) => return Some(NetworkSetupAction::Connected(connection, is_host)),
+                    Err(e) => self.error = Some(format!("Connection failed: {}", e)),
+                }
+            }
+        }
+
+        ui.vertical_centered(|ui| {
+            ui.add_space(50.0);
+            ui.heading("LAN Versus");
+            ui.add_space(30.0);
+
+            let waiting = self.pending.is_some();
+
+            ui.horizontal(|ui| {
+                ui.label("Port:");
+                ui.add_enabled(!waiting, egui::TextEdit::singleline(&mut self.port).desired_width(80.0));
+                if ui.add_enabled(!waiting, egui::Button::new("Host")).clicked() {
+                    self.error = None;
+                    match self.port.trim().parse::<u16>() {
+                        Ok(port) => match network::host(port) {
+                            Ok(pending) => self.pending = Some(pending),
+                            Err(e) => self.error = Some(format!("Could not host on port {}: {}", port, e)),
+                        },
+                        Err(_) => self.error = Some("Port must be a number from 0 to 65535".to_string()),
+                    }
+                }
+            });
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                ui.label("Address:");
+                ui.add_enabled(!waiting, egui::TextEdit::singleline(&mut self.address).desired_width(160.0));
+                if ui.add_enabled(!waiting, egui::Button::new("Join")).clicked() {
+                    self.error = None;
+                    self.pending = Some(network::join(self.address.trim().to_string()));
+                }
+            });
+            ui.add_space(20.0);
+
+            match &self.pending {
+                Some(PendingConnection::Hosting(_)) => {
+                    ui.label(format!("Waiting for a player to join on port {}...", self.port.trim()));
+                },
+                Some(PendingConnection::Joining(_)) => {
+                    ui.label(format!("Connecting to {}...", self.address.trim()));
+                },
+                None => {},
+            }
+            if waiting && ui.button("Cancel").clicked() {
+                self.pending = None;
+            }
+
+            if let Some(error) = &self.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+
+            ui.add_space(20.0);
+            if ui.button("Back to Menu").clicked() {
+                self.pending = None;
+                action = Some(NetworkSetupAction::Back);
+            }
+        });
+
+        action
+    }
+}
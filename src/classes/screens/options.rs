@@ -2,22 +2,22 @@ use eframe::egui;
 
 use crate::enums::game_actions::GameAction;
 use crate::classes::game_options::GameOptions;
+use crate::classes::randomizer::RandomizerKind;
+use crate::classes::perfect_clear::PcSetup;
+use crate::classes::opener::Opener;
+use crate::classes::engine::MARATHON_LINE_CAPS;
+use egui::ahash::HashMap;
 
 pub enum OptionSelectionAction {
     Save,
     Cancel,
 }
 
+#[derive(Default)]
 pub struct OptionsScreen {
     pub waiting_for_key: Option<GameAction>,
-}
-
-impl Default for OptionsScreen {
-    fn default() -> Self {
-        Self {
-            waiting_for_key: None,
-        }
-    }
+    // Whether the key being rebound is one of the versus player 2 keys
+    pub waiting_for_player2: bool,
 }
 
 impl OptionsScreen {
@@ -28,7 +28,12 @@ impl OptionsScreen {
         if let Some(waiting_action) = &self.waiting_for_key {
             if let Some(pressed_key) = detect_key_press(ui) {
                 // Assign the new key
-                if let Some(key_binding) = options.key_bindings.get_mut(waiting_action) {
+                let bindings = if self.waiting_for_player2 {
+                    &mut options.player2_key_bindings
+                } else {
+                    &mut options.key_bindings
+                };
+                if let Some(key_binding) = bindings.get_mut(waiting_action) {
                     *key_binding = pressed_key;
                 }
                 self.waiting_for_key = None;
@@ -67,6 +72,72 @@ impl OptionsScreen {
                 ui.label("ARR Delay:");
                 ui.add(egui::Slider::new(&mut options.arr_delay, 0.0..=83.0).suffix(" ms"));
             });
+            ui.add_space(10.0);
+
+            // Soft drop factor slider
+            ui.horizontal(|ui| {
+                ui.label("Soft Drop Factor:");
+                ui.add_enabled(
+                    !options.instant_soft_drop,
+                    egui::Slider::new(&mut options.soft_drop_factor, 1.0..=40.0).suffix("x"),
+                );
+                ui.checkbox(&mut options.instant_soft_drop, "Instant (∞)");
+            });
+            ui.add_space(10.0);
+
+            // Piece randomizer, applies from the next game
+            ui.horizontal(|ui| {
+                ui.label("Randomizer:");
+                egui::ComboBox::from_id_salt("options_randomizer")
+                    .selected_text(options.randomizer.display_name())
+                    .show_ui(ui, |ui| {
+                        for kind in RandomizerKind::ALL {
+                            ui.selectable_value(&mut options.randomizer, kind, kind.display_name());
+                        }
+                    });
+            });
+            ui.add_space(10.0);
+
+            // Lines that finish a Marathon, endless by default, a preset or any custom cap
+            ui.horizontal(|ui| {
+                ui.label("Marathon Length:");
+                let mut endless = options.marathon_line_cap.is_none();
+                if ui.checkbox(&mut endless, "Endless").changed() {
+                    options.marathon_line_cap = if endless { None } else { Some(MARATHON_LINE_CAPS[0]) };
+                }
+                if let Some(cap) = &mut options.marathon_line_cap {
+                    for preset in MARATHON_LINE_CAPS {
+                        ui.selectable_value(cap, preset, format!("{} lines", preset));
+                    }
+                    ui.add(egui::DragValue::new(cap).range(10..=999).suffix(" lines"));
+                }
+            });
+            ui.add_space(10.0);
+
+            // Board PC practice starts from
+            ui.horizontal(|ui| {
+                ui.label("PC Practice Setup:");
+                egui::ComboBox::from_id_salt("options_pc_setup")
+                    .selected_text(options.pc_setup.display_name())
+                    .show_ui(ui, |ui| {
+                        for setup in PcSetup::ALL {
+                            ui.selectable_value(&mut options.pc_setup, setup, setup.display_name());
+                        }
+                    });
+            });
+            ui.add_space(10.0);
+
+            // Opener the Opener Trainer drills
+            ui.horizontal(|ui| {
+                ui.label("Opener:");
+                egui::ComboBox::from_id_salt("options_opener")
+                    .selected_text(options.opener.display_name())
+                    .show_ui(ui, |ui| {
+                        for opener in Opener::ALL {
+                            ui.selectable_value(&mut options.opener, opener, opener.display_name());
+                        }
+                    });
+            });
             ui.add_space(20.0);
 
             // Key bindings
@@ -84,32 +155,12 @@ impl OptionsScreen {
                 GameAction::PauseGame,
             ];
             
-            for action_key in actions.iter() {
-                ui.horizontal(|ui| {
-                    ui.label(format!("{:?}:", action_key));
-                    ui.add_space(10.0);
-                    
-                    let is_waiting = self.waiting_for_key.as_ref() == Some(action_key);
-                    
-                    if let Some(current_key) = options.key_bindings.get(action_key) {
-                        let button_text = if is_waiting {
-                            "Press any key...".to_string()
-                        } else {
-                            format!("{:?}", current_key)
-                        };
-                        
-                        let button = ui.button(button_text);
-                        
-                        if button.clicked() && !is_waiting {
-                            self.waiting_for_key = Some(*action_key);
-                        }
-                        
-                        if is_waiting {
-                            button.highlight();
-                        }
-                    }
-                });
-            }
+            self.draw_bindings(ui, &actions, &options.key_bindings, false);
+
+            ui.add_space(20.0);
+            ui.label("Player 2 Key Bindings (Versus):");
+            ui.add_space(10.0);
+            self.draw_bindings(ui, &actions[..7], &options.player2_key_bindings, true);
             
             if self.waiting_for_key.is_some() {
                 ui.add_space(10.0);
@@ -130,12 +181,50 @@ impl OptionsScreen {
 
         action
     }
+
+    // One rebind button per action
+    fn draw_bindings(
+        &mut self,
+        ui: &mut egui::Ui,
+        actions: &[GameAction],
+        bindings: &HashMap<GameAction, egui::Key>,
+        player2: bool,
+    ) {
+        for action_key in actions.iter() {
+            ui.horizontal(|ui| {
+                ui.label(format!("{:?}:", action_key));
+                ui.add_space(10.0);
+
+                let is_waiting = self.waiting_for_key.as_ref() == Some(action_key)
+                    && self.waiting_for_player2 == player2;
+
+                if let Some(current_key) = bindings.get(action_key) {
+                    let button_text = if is_waiting {
+                        "Press any key...".to_string()
+                    } else {
+                        format!("{:?}", current_key)
+                    };
+
+                    let button = ui.button(button_text);
+
+                    if button.clicked() && !is_waiting {
+                        self.waiting_for_key = Some(*action_key);
+                        self.waiting_for_player2 = player2;
+                    }
+
+                    if is_waiting {
+                        button.highlight();
+                    }
+                }
+            });
+        }
+    }
 }
 
 fn detect_key_press(ui: &egui::Ui) -> Option<egui::Key> {
     ui.input(|i| {
         // Check all common keys
-        for key in [
+        [
             egui::Key::ArrowUp,
             egui::Key::ArrowDown,
             egui::Key::ArrowLeft,
@@ -152,11 +241,8 @@ fn detect_key_press(ui: &egui::Ui) -> Option<egui::Key> {
             egui::Key::Num3, egui::Key::Num4, egui::Key::Num5,
             egui::Key::Num6, egui::Key::Num7, egui::Key::Num8,
             egui::Key::Num9,
-        ] {
-            if i.key_pressed(key) {
-                return Some(key);
-            }
-        }
-        None
+        ]
+        .into_iter()
+        .find(|key| i.key_pressed(*key))
     })
 }
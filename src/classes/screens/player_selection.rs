@@ -10,6 +10,7 @@ pub struct PlayerInfo {
     pub name: String,
 }
 
+#[allow(dead_code)]
 pub fn draw(ui: &mut egui::Ui) -> Option<PlayerSelectionAction> {
     // This will be called from screen_manager which will pass the players list
     // For now, we'll update this to accept players as parameter
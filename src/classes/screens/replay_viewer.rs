@@ -0,0 +1,87 @@
+use eframe::egui;
+
+use crate::classes::engine::FRAME_TIME;
+use crate::classes::game::draw_engine;
+use crate::classes::replay::{Replay, ReplayPlayer};
+
+const PLAYBACK_SPEEDS: [f32; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+
+pub enum ReplayViewerAction {
+    Close,
+}
+
+pub struct ReplayViewer {
+    player: ReplayPlayer,
+    paused: bool,
+    speed: f32,
+    pub from_leaderboard: bool,
+}
+
+impl ReplayViewer {
+    pub fn new(replay: Replay, from_leaderboard: bool) -> Self {
+        Self {
+            player: ReplayPlayer::new(replay),
+            paused: false,
+            speed: 1.0,
+            from_leaderboard,
+        }
+    }
+
+    pub fn draw(&mut self, ui: &mut egui::Ui, ghost_alpha: u8) -> Option<ReplayViewerAction> {
+        let mut action = None;
+
+        if !self.paused {
+            let delta_time = ui.input(|i| i.stable_dt);
+            self.player.step(delta_time, self.speed);
+        }
+
+        ui.vertical_centered(|ui| {
+            ui.heading("Replay");
+            ui.add_space(10.0);
+
+            // Playback controls
+            ui.horizontal(|ui| {
+                let play_label = if self.paused { "▶ Play" } else { "⏸ Pause" };
+                if ui.button(play_label).clicked() {
+                    self.paused = !self.paused;
+                }
+
+                // Frame stepping only makes sense while paused
+                if ui.add_enabled(self.paused && !self.player.is_finished(), egui::Button::new("Step ⏭")).clicked() {
+                    self.player.tick();
+                }
+
+                if ui.button("⟲ Restart").clicked() {
+                    self.player.restart();
+                }
+
+                ui.label("Speed:");
+                egui::ComboBox::from_id_salt("replay_speed")
+                    .selected_text(format!("{}x", self.speed))
+                    .show_ui(ui, |ui| {
+                        for speed in PLAYBACK_SPEEDS {
+                            ui.selectable_value(&mut self.speed, speed, format!("{}x", speed));
+                        }
+                    });
+
+                let frame = self.player.get_frame();
+                let seconds = frame as f32 * FRAME_TIME;
+                ui.label(format!("Frame {} ({}:{:05.2})", frame, (seconds / 60.0) as u32, seconds % 60.0));
+            });
+
+            if self.player.is_finished() {
+                ui.colored_label(egui::Color32::YELLOW, "Replay finished");
+            }
+            ui.add_space(10.0);
+        });
+
+        draw_engine(self.player.engine(), ui, ghost_alpha);
+
+        ui.add_space(10.0);
+        if ui.button("Back").clicked() {
+            action = Some(ReplayViewerAction::Close);
+        }
+
+        action
+    }
+}
@@ -0,0 +1,56 @@
+use eframe::egui;
+use crate::classes::game::draw_engine;
+use crate::classes::versus::VersusMatch;
+
+pub enum VersusAction {
+    Rematch,
+    BackToMenu,
+}
+
+pub fn draw(ui: &mut egui::Ui, versus_match: &mut VersusMatch, ghost_alpha: u8) -> Option<VersusAction> {
+    let mut action = None;
+
+    versus_match.update(ui.ctx());
+
+    // Both boards side by side, each with its own garbage meter
+    ui.horizontal_top(|ui| {
+        for player in &mut versus_match.players {
+            ui.vertical(|ui| {
+                ui.label(egui::RichText::new(&player.name).size(18.0).strong());
+                if player.bot.is_none() {
+                    let (wins, losses) = player.record;
+                    ui.label(format!("Record: {}W - {}L", wins, losses));
+                }
+                ui.add_space(5.0);
+                draw_engine(&mut player.game.engine, ui, ghost_alpha);
+            });
+            ui.add_space(20.0);
+        }
+    });
+
+    ui.add_space(10.0);
+    ui.vertical_centered(|ui| {
+        if versus_match.is_finished() {
+            let result = match versus_match.get_winner() {
+                Some(winner) => format!("{} wins!", versus_match.players[winner].name),
+                None => "Draw!".to_string(),
+            };
+            ui.heading(result);
+            ui.add_space(10.0);
+            if ui.button("Rematch").clicked() {
+                action = Some(VersusAction::Rematch);
+            }
+            ui.add_space(10.0);
+            if ui.button("Back to Menu").clicked() {
+                action = Some(VersusAction::BackToMenu);
+            }
+        } else if versus_match.is_paused() {
+            ui.heading("PAUSED");
+            if ui.button("Back to Menu").clicked() {
+                action = Some(VersusAction::BackToMenu);
+            }
+        }
+    });
+
+    action
+}
@@ -0,0 +1,66 @@
+use eframe::egui;
+use super::player_selection::PlayerInfo;
+use crate::enums::cpu_difficulty::CpuDifficulty;
+
+pub enum VersusSetupAction {
+    StartMatch(i64),
+    StartCpuMatch(CpuDifficulty),
+    Back,
+}
+
+// Pick player 2 from the other players or the CPU, player 1 is whoever is signed in
+pub fn draw(ui: &mut egui::Ui, player_name: &str, opponents: &[PlayerInfo], difficulty: &mut CpuDifficulty) -> Option<VersusSetupAction> {
+    let mut action = None;
+
+    ui.vertical_centered(|ui| {
+        ui.add_space(50.0);
+        ui.heading("Local Versus");
+        ui.add_space(10.0);
+        ui.label(format!("Player 1: {}", player_name));
+        ui.add_space(20.0);
+        ui.label("Choose Player 2:");
+        ui.add_space(10.0);
+
+        if opponents.is_empty() {
+            ui.label("No other players found. Create a second player first.");
+            ui.add_space(20.0);
+        } else {
+            egui::ScrollArea::vertical()
+                .max_height(350.0)
+                .show(ui, |ui| {
+                    for opponent in opponents {
+                        ui.add_space(5.0);
+                        if ui.add_sized(
+                            [400.0, 40.0],
+                            egui::Button::new(&opponent.name)
+                        ).clicked() {
+                            action = Some(VersusSetupAction::StartMatch(opponent.id));
+                        }
+                    }
+                });
+            ui.add_space(20.0);
+        }
+
+        ui.label("Or practise against the CPU:");
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("versus_cpu_difficulty")
+                .selected_text(difficulty.display_name())
+                .show_ui(ui, |ui| {
+                    for level in CpuDifficulty::ALL {
+                        ui.selectable_value(difficulty, level, level.display_name());
+                    }
+                });
+            if ui.button("Play vs CPU").clicked() {
+                action = Some(VersusSetupAction::StartCpuMatch(*difficulty));
+            }
+        });
+        ui.add_space(20.0);
+
+        if ui.button("Back to Menu").clicked() {
+            action = Some(VersusSetupAction::Back);
+        }
+    });
+
+    action
+}
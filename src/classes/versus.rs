@@ -0,0 +1,145 @@
+use super::bot::Bot;
+use super::game::Game;
+use super::game_options::GameOptions;
+use crate::enums::{cpu_difficulty::CpuDifficulty, game_actions::GameAction, game_modes::GameMode, states::GameState};
+
+// Boards are drawn smaller so both fit side by side
+const VERSUS_CELL_SIZE: f32 = 20.0;
+
+pub struct VersusPlayer {
+    pub id: Option<i64>,
+    pub name: String,
+    pub game: Game,
+    // Wins and losses against anyone, refreshed after each match
+    pub record: (u32, u32),
+    // Plays this side instead of the keyboard
+    pub bot: Option<Bot>,
+}
+
+// Two games on one keyboard. Player 1 plays with the usual key bindings and
+// player 2 with their own or the CPU, garbage from line clears goes to the
+// other side.
+pub struct VersusMatch {
+    pub players: [VersusPlayer; 2],
+    finished: bool,
+    winner: Option<usize>,
+    result_taken: bool,
+}
+
+impl VersusMatch {
+    pub fn new(options: &GameOptions, players: [(Option<i64>, String); 2]) -> Self {
+        let [(id1, name1), (id2, name2)] = players;
+
+        // Restarting would only restart one side, so it's left out of a match
+        let mut player1_options = options.clone();
+        player1_options.key_bindings.remove(&GameAction::RestartGame);
+
+        let mut versus_match = Self {
+            players: [
+                VersusPlayer { id: id1, name: name1, game: Game::new(player1_options), record: (0, 0), bot: None },
+                VersusPlayer { id: id2, name: name2, game: Game::new(options.for_player2()), record: (0, 0), bot: None },
+            ],
+            finished: false,
+            winner: None,
+            result_taken: false,
+        };
+        versus_match.start();
+        versus_match
+    }
+
+    // Player 1 against the CPU, which gets no keys at all
+    pub fn against_cpu(options: &GameOptions, player1: (Option<i64>, String), difficulty: CpuDifficulty) -> Self {
+        let cpu_name = format!("CPU ({})", difficulty.display_name());
+        let mut versus_match = Self::new(options, [player1, (None, cpu_name)]);
+
+        let cpu = &mut versus_match.players[1];
+        cpu.game.options.key_bindings.clear();
+        cpu.bot = Some(Bot::new(difficulty, cpu.game.engine.queue.get_seed()));
+        versus_match
+    }
+
+    // Whether player 2 is the CPU, CPU matches aren't recorded
+    pub fn is_against_cpu(&self) -> bool {
+        self.players[1].bot.is_some()
+    }
+
+    pub fn start(&mut self) {
+        let seed = rand::random();
+        for player in &mut self.players {
+            player.game.set_mode(GameMode::Versus);
+            player.game.start_game_with_seed(seed);
+            player.game.engine.board.cell_size = VERSUS_CELL_SIZE;
+            if let Some(bot) = &mut player.bot {
+                bot.reset(seed);
+            }
+        }
+        self.finished = false;
+        self.winner = None;
+        self.result_taken = false;
+    }
+
+    pub fn update(&mut self, ctx: &egui::Context) {
+        if self.finished {
+            return;
+        }
+
+        // Player 1's pause key pauses both sides
+        let [player1, player2] = &mut self.players;
+        player1.game.update(ctx);
+        match player1.game.get_state() {
+            GameState::Paused => player2.game.set_state(GameState::Paused),
+            GameState::Playing if player2.game.get_state() == GameState::Paused => {
+                player2.game.set_state(GameState::Playing)
+            }
+            _ => {}
+        }
+        player2.game.update(ctx);
+        if let Some(bot) = &mut player2.bot {
+            if player2.game.get_state() == GameState::Playing {
+                bot.update(&mut player2.game.engine);
+            }
+        }
+
+        // Exchange attacks, cancelling happens when the garbage is received
+        let attack1 = player1.game.engine.take_attack();
+        let attack2 = player2.game.engine.take_attack();
+        player2.game.engine.receive_garbage(attack1);
+        player1.game.engine.receive_garbage(attack2);
+
+        let over1 = player1.game.engine.is_game_over();
+        let over2 = player2.game.engine.is_game_over();
+        if over1 || over2 {
+            self.finished = true;
+            // Both topping out on the same frame is a draw
+            self.winner = match (over1, over2) {
+                (true, false) => Some(1),
+                (false, true) => Some(0),
+                _ => None,
+            };
+            for player in &mut self.players {
+                player.game.set_state(GameState::GameOver);
+            }
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.players[0].game.get_state() == GameState::Paused
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn get_winner(&self) -> Option<usize> {
+        self.winner
+    }
+
+    // The finished match's winner, handed out once so it's only recorded once
+    pub fn take_result(&mut self) -> Option<Option<usize>> {
+        if !self.finished || self.result_taken {
+            return None;
+        }
+        self.result_taken = true;
+        Some(self.winner)
+    }
+}
@@ -0,0 +1,46 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CpuDifficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl CpuDifficulty {
+    pub const ALL: [CpuDifficulty; 4] = [
+        CpuDifficulty::Easy,
+        CpuDifficulty::Medium,
+        CpuDifficulty::Hard,
+        CpuDifficulty::Expert,
+    ];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            CpuDifficulty::Easy => "Easy",
+            CpuDifficulty::Medium => "Medium",
+            CpuDifficulty::Hard => "Hard",
+            CpuDifficulty::Expert => "Expert",
+        }
+    }
+
+    // Fastest the CPU will place pieces
+    pub fn pieces_per_second(&self) -> f32 {
+        match self {
+            CpuDifficulty::Easy => 0.6,
+            CpuDifficulty::Medium => 1.2,
+            CpuDifficulty::Hard => 2.0,
+            CpuDifficulty::Expert => 3.0,
+        }
+    }
+
+    // Chance of placing a piece in a worse spot than the best one found
+    pub fn mistake_rate(&self) -> f64 {
+        match self {
+            CpuDifficulty::Easy => 0.35,
+            CpuDifficulty::Medium => 0.15,
+            CpuDifficulty::Hard => 0.05,
+            CpuDifficulty::Expert => 0.0,
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum GameMode {
+    #[default]
+    Marathon,
+    Sprint,
+    Ultra,
+    Master,
+    Dig,
+    Survival,
+    PerfectClear,
+    Opener,
+    // Local two player match, started from its own screen
+    Versus,
+    // Free play from a board editor position
+    Puzzle,
+}
+
+impl GameMode {
+    // Modes that can be picked from the menu
+    pub const ALL: [GameMode; 8] = [
+        GameMode::Marathon,
+        GameMode::Sprint,
+        GameMode::Ultra,
+        GameMode::Master,
+        GameMode::Dig,
+        GameMode::Survival,
+        GameMode::PerfectClear,
+        GameMode::Opener,
+    ];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            GameMode::Marathon => "Marathon",
+            GameMode::Sprint => "40 Lines Sprint",
+            GameMode::Ultra => "2 Minute Ultra",
+            GameMode::Master => "Master (20G)",
+            GameMode::Dig => "Dig Race",
+            GameMode::Survival => "Survival",
+            GameMode::PerfectClear => "PC Practice",
+            GameMode::Opener => "Opener Trainer",
+            GameMode::Versus => "Versus",
+            GameMode::Puzzle => "Custom Puzzle",
+        }
+    }
+
+    // Stable name stored in the database
+    pub fn db_name(&self) -> &'static str {
+        match self {
+            GameMode::Marathon => "marathon",
+            GameMode::Sprint => "sprint",
+            GameMode::Ultra => "ultra",
+            GameMode::Master => "master",
+            GameMode::Dig => "dig",
+            GameMode::Survival => "survival",
+            GameMode::PerfectClear => "pc_practice",
+            GameMode::Opener => "opener",
+            GameMode::Versus => "versus",
+            GameMode::Puzzle => "puzzle",
+        }
+    }
+
+    pub fn from_db_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .chain(&[GameMode::Versus, GameMode::Puzzle])
+            .copied()
+            .find(|mode| mode.db_name() == name)
+    }
+}
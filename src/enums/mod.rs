@@ -1,2 +1,4 @@
 pub mod states;
-pub mod game_actions;
\ No newline at end of file
+pub mod game_actions;
+pub mod game_modes;
+pub mod cpu_difficulty;
\ No newline at end of file
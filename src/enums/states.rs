@@ -8,4 +8,10 @@ pub enum GameState {
     GameOver,
     Leaderboard,
     Options,
+    Replay,
+    VersusSetup,
+    Versus,
+    NetworkSetup,
+    NetworkVersus,
+    BoardEditor,
 }
\ No newline at end of file
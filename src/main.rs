@@ -6,6 +6,7 @@ use crate::classes::database::database::DbManager;
 use classes::game::Game;
 use classes::screen_manager::{ScreenManager, ScreenAction};
 use classes::player::Player;
+use classes::versus::VersusMatch;
 use eframe::egui;
 
 use crate::classes::game_options::GameOptions;
@@ -89,7 +90,10 @@ impl eframe::App for RusttrisApp {
             
             if let Some(player_id) = self.player.id {
                 println!("Saving game for player ID: {}", player_id);
-                self.db_manager.save_game(player_id, &game_stats).unwrap();
+                let game_id = self.db_manager.save_game(player_id, &game_stats).unwrap();
+                if let Err(e) = self.db_manager.save_replay(game_id, &self.game.engine.get_replay()) {
+                    println!("Error saving replay: {}", e);
+                }
             } else {
                 println!("WARNING: Player has no ID, game not saved to database!");
             }
@@ -115,8 +119,8 @@ impl eframe::App for RusttrisApp {
                 let options_ptr = unsafe { &mut (*game_ptr).options as *mut GameOptions };
                 if let Some(action) = self.screen_manager.draw(&mut self.game, ui, &self.player.name, &self.db_manager, unsafe { &mut *options_ptr }) {
                     match action {
-                        ScreenAction::StartGame => {
-                            self.game.reset_game();
+                        ScreenAction::StartGame(mode) => {
+                            self.game.set_mode(mode);
                             self.game.start_game()
                         },
                         ScreenAction::ResumeGame => self.game.resume_game(),
@@ -179,6 +183,82 @@ impl eframe::App for RusttrisApp {
                         ScreenAction::ShowOptions => {
                             self.game.set_state(crate::enums::states::GameState::Options);
                         },
+                        ScreenAction::WatchReplay(game_id) => {
+                            match self.db_manager.get_replay(game_id) {
+                                Ok(Some(replay)) => {
+                                    self.screen_manager.open_replay(replay, true);
+                                    self.game.set_state(crate::enums::states::GameState::Replay);
+                                },
+                                Ok(None) => println!("No replay saved for game ID: {}", game_id),
+                                Err(e) => println!("Error loading replay: {}", e),
+                            }
+                        },
+                        ScreenAction::ShowVersusSetup => {
+                            self.game.set_state(crate::enums::states::GameState::VersusSetup);
+                        },
+                        ScreenAction::StartVersus(opponent_id) => {
+                            match self.db_manager.get_player(opponent_id) {
+                                Ok(Some(opponent)) => {
+                                    let mut versus_match = VersusMatch::new(&self.game.options, [
+                                        (self.player.id, self.player.name.clone()),
+                                        (Some(opponent_id), opponent.name),
+                                    ]);
+                                    for player in &mut versus_match.players {
+                                        if let Some(id) = player.id {
+                                            player.record = self.db_manager.get_versus_record(id).unwrap_or_default();
+                                        }
+                                    }
+                                    self.screen_manager.open_versus(versus_match);
+                                    self.game.set_state(crate::enums::states::GameState::Versus);
+                                },
+                                Ok(None) => println!("No player with ID: {}", opponent_id),
+                                Err(e) => println!("Error loading opponent: {}", e),
+                            }
+                        },
+                        ScreenAction::StartCpuVersus(difficulty) => {
+                            let versus_match = VersusMatch::against_cpu(
+                                &self.game.options,
+                                (self.player.id, self.player.name.clone()),
+                                difficulty,
+                            );
+                            self.screen_manager.open_versus(versus_match);
+                            self.game.set_state(crate::enums::states::GameState::Versus);
+                        },
+                        ScreenAction::VersusFinished(winner) => {
+                            // Games against the CPU are only practice and aren't recorded
+                            if let Some(versus_match) = self.screen_manager.versus_match().filter(|m| !m.is_against_cpu()) {
+                                if let [Some(player1_id), Some(player2_id)] = versus_match.players.each_ref().map(|p| p.id) {
+                                    let winner_id = winner.and_then(|index| versus_match.players[index].id);
+                                    if let Err(e) = self.db_manager.save_versus_match(player1_id, player2_id, winner_id) {
+                                        println!("Error saving versus match: {}", e);
+                                    }
+                                    for player in &mut versus_match.players {
+                                        player.record = self.db_manager.get_versus_record(player.id.unwrap()).unwrap_or_default();
+                                    }
+                                    (self.player.stats.versus_wins, self.player.stats.versus_losses) = versus_match.players[0].record;
+                                } else {
+                                    println!("WARNING: Player has no ID, versus match not saved to database!");
+                                }
+                            }
+                        },
+                        ScreenAction::ShowNetworkSetup => {
+                            self.game.set_state(crate::enums::states::GameState::NetworkSetup);
+                        },
+                        ScreenAction::ShowNetworkVersus => {
+                            self.game.set_state(crate::enums::states::GameState::NetworkVersus);
+                        },
+                        ScreenAction::ShowBoardEditor => {
+                            self.game.set_state(crate::enums::states::GameState::BoardEditor);
+                        },
+                        ScreenAction::StartPuzzle(puzzle) => {
+                            self.game.set_puzzle(puzzle);
+                            self.game.set_mode(crate::enums::game_modes::GameMode::Puzzle);
+                            self.game.start_game()
+                        },
+                        ScreenAction::WatchLastReplay => {
+                            self.screen_manager.open_replay(self.game.engine.get_replay(), false);
+                            self.game.set_state(crate::enums::states::GameState::Replay);
+                        },
                     }
                 }
             });